@@ -0,0 +1,660 @@
+use crate::dna;
+use crate::io_utils::{FaReader, FqReader, FxWriter};
+use crate::record::RecordType;
+use crate::sub_cli::{AdapterKind, DetectAdapterArgs, TrimAdapterArgs};
+use crate::trim::PolyTail;
+use std::collections::HashMap;
+
+/// Built-in 3' adapters: (set, name, sequence)
+const BUILTIN_ADAPTERS: &[(AdapterKind, &str, &str)] = &[
+    (
+        AdapterKind::Truseq,
+        "TruSeq_Read1",
+        "AGATCGGAAGAGCACACGTCTGAACTCCAGTCA",
+    ),
+    (
+        AdapterKind::Truseq,
+        "TruSeq_Read2",
+        "AGATCGGAAGAGCGTCGTGTAGGGAAAGAGTGT",
+    ),
+    (AdapterKind::Nextera, "Nextera", "CTGTCTCTTATACACATCT"),
+    (AdapterKind::SmallRna, "SmallRNA", "TGGAATTCTCGGGTGCCAAGG"),
+];
+
+/// The longest adapter assembled by [`detect_adapter_fastx`]
+const MAX_DETECTED_LEN: usize = 64;
+
+/// Parses FASTA/Q file and trims the 3' adapters.
+/// Outputs the trimmed reads to [`std::io::stdout()`] and the number of trimmed reads per adapter to [`std::io::stderr()`].
+///
+/// An adapter may start anywhere in a read, and a prefix of it is matched at the 3' end (a partial overlap).
+/// A match needs at least `--min-overlap` bases and at most `floor(overlap * --error-rate)` mismatches.
+/// The read is cut at the leftmost match of all adapters.
+///
+/// # Arguments
+///
+/// Check the arguments by `--help`
+///
+/// # Errors
+///
+/// Return an error if the adapters are invalid or the operation cannot be completed.
+pub fn trim_adapter_fastx(
+    fx_path: &str,
+    tparas: &TrimAdapterArgs,
+    is_fasta: bool,
+) -> Result<(), std::io::Error> {
+    let trimmer = AdapterTrimmer::new(
+        load_adapters(&tparas.builtin, tparas.adapter_fa.as_deref())?,
+        tparas.error_rate.unwrap_or(0.1),
+        tparas.min_overlap.unwrap_or(3),
+    );
+    let min_len = tparas.min_length.unwrap_or(0);
+    let mut writer = FxWriter::new(is_fasta);
+    let poly_tail = PolyTail::from(tparas.poly_x, tparas.poly_min_len, tparas.poly_error_rate);
+    let mut stats = TrimStats::new(trimmer.adapters.len());
+    stats.by_poly = poly_tail.as_ref().map(|_| (0, 0));
+    let mut trim_record = |read: &dyn RecordType| -> Result<(), std::io::Error> {
+        let end = trimmer.trim(read.seq(), &mut stats);
+        let end = stats.trim_poly(poly_tail.as_ref(), &read.seq()[..end]);
+        if end < min_len {
+            stats.discarded += 1;
+            return Ok(());
+        }
+        let qual = if is_fasta {
+            &[][..]
+        } else {
+            &read.qual()[..end]
+        };
+        writer.write(read.id(), &read.seq()[..end], read.desc(), qual)
+    };
+    if is_fasta {
+        let fa_iter = FaReader::new(fx_path)?;
+        for record in fa_iter.records() {
+            match record {
+                Ok(read) => trim_record(&read)?,
+                Err(e) => eprintln!("Error read FASTA: {}", e),
+            }
+        }
+    } else {
+        let fq_iter = FqReader::new(fx_path)?;
+        for record in fq_iter.records() {
+            match record {
+                Ok(read) => trim_record(&read)?,
+                Err(e) => eprintln!("Error read FASTQ: {}", e),
+            }
+        }
+    }
+    stats.report(&trimmer.adapters);
+    Ok(())
+}
+
+/// Parses paired FASTQ files and trims both mates at the insert boundary or by the 3' adapters.
+/// Outputs the pairs interleaved to [`std::io::stdout()`] (or to `--out1` and `--out2`)
+/// and the number of trimmed reads to [`std::io::stderr()`].
+///
+/// Read 1 is aligned with the reverse complement of read 2 (see [`find_insert`]). If the insert is shorter than a mate,
+/// the mates are cut at the insert length, otherwise each mate is trimmed by the adapters as [`trim_adapter_fastx`] does.
+/// A pair is discarded if either mate is shorter than `--min-length`, so the mates stay in sync.
+///
+/// # Arguments
+///
+/// Check the arguments by `--help`
+///
+/// # Errors
+///
+/// Return an error if the files have different numbers of reads, or the operation cannot be completed.
+pub fn trim_adapter_paired(
+    fq1_path: &str,
+    fq2_path: &str,
+    tparas: &TrimAdapterArgs,
+) -> Result<(), std::io::Error> {
+    let error_rate = tparas.error_rate.unwrap_or(0.1);
+    let trimmer = AdapterTrimmer::new(
+        load_adapters(&tparas.builtin, tparas.adapter_fa.as_deref())?,
+        error_rate,
+        tparas.min_overlap.unwrap_or(3),
+    );
+    let min_len = tparas.min_length.unwrap_or(0);
+    let min_insert_overlap = tparas.min_insert_overlap.unwrap_or(20);
+    let (mut writer1, mut writer2) = match (&tparas.out1, &tparas.out2) {
+        (Some(out1), Some(out2)) => (
+            FxWriter::from_path(out1, false)?,
+            Some(FxWriter::from_path(out2, false)?),
+        ),
+        _ => (FxWriter::new(false), None),
+    };
+    let poly_tail = PolyTail::from(tparas.poly_x, tparas.poly_min_len, tparas.poly_error_rate);
+    let mut stats = TrimStats::new(trimmer.adapters.len());
+    stats.by_insert = Some((0, 0));
+    stats.by_poly = poly_tail.as_ref().map(|_| (0, 0));
+    let mut fq2_iter = FqReader::new(fq2_path)?.records();
+    let unpaired = || {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!(
+                "{} and {} have different numbers of reads",
+                fq1_path, fq2_path
+            ),
+        )
+    };
+    for record1 in FqReader::new(fq1_path)?.records() {
+        let (read1, read2) = match (record1, fq2_iter.next().ok_or_else(unpaired)?) {
+            (Ok(read1), Ok(read2)) => (read1, read2),
+            (Err(e), _) | (_, Err(e)) => {
+                eprintln!("Error read FASTQ: {}", e);
+                continue;
+            }
+        };
+        let (len1, len2) = (read1.seq().len(), read2.seq().len());
+        let (end1, end2) =
+            match find_insert(read1.seq(), read2.seq(), min_insert_overlap, error_rate) {
+                Some(insert) => {
+                    let ends = (insert.min(len1), insert.min(len2));
+                    stats.add_insert(len1 - ends.0);
+                    stats.add_insert(len2 - ends.1);
+                    ends
+                }
+                None => (
+                    trimmer.trim(read1.seq(), &mut stats),
+                    trimmer.trim(read2.seq(), &mut stats),
+                ),
+            };
+        let end1 = stats.trim_poly(poly_tail.as_ref(), &read1.seq()[..end1]);
+        let end2 = stats.trim_poly(poly_tail.as_ref(), &read2.seq()[..end2]);
+        if end1 < min_len || end2 < min_len {
+            stats.discarded += 2;
+            continue;
+        }
+        writer1.write(
+            read1.id(),
+            &read1.seq()[..end1],
+            read1.desc(),
+            &read1.qual()[..end1],
+        )?;
+        writer2.as_mut().unwrap_or(&mut writer1).write(
+            read2.id(),
+            &read2.seq()[..end2],
+            read2.desc(),
+            &read2.qual()[..end2],
+        )?;
+    }
+    if fq2_iter.next().is_some() {
+        return Err(unpaired());
+    }
+    stats.report(&trimmer.adapters);
+    Ok(())
+}
+
+/// The insert length of a pair by aligning read 1 with the reverse complement of read 2.
+/// Only the inserts shorter than the longer mate are searched (from the longest), as the mates contain adapters then.
+///
+/// An insert of length `L` puts base `i` of read 1 at `i + len2 - L` of the reverse complement of read 2.
+/// The alignment needs at least `min_overlap` bases and at most `floor(overlap * error_rate)` mismatches (N is a mismatch).
+pub fn find_insert(seq1: &[u8], seq2: &[u8], min_overlap: usize, error_rate: f64) -> Option<usize> {
+    let mut rc2 = seq2.to_ascii_uppercase();
+    dna::revcomp(&mut rc2);
+    let seq1 = seq1.to_ascii_uppercase();
+    let (len1, len2) = (seq1.len(), rc2.len());
+    for insert in (min_overlap.max(1)..len1.max(len2)).rev() {
+        let (lo, hi) = (insert.saturating_sub(len2), insert.min(len1));
+        if hi < lo + min_overlap {
+            continue;
+        }
+        let max_errors = ((hi - lo) as f64 * error_rate) as usize;
+        let mut errors = 0;
+        let matched = (lo..hi).all(|i| {
+            // i >= insert - len2, so the index is not negative
+            let (a, b) = (seq1[i], rc2[i + len2 - insert]);
+            if a != b || a == b'N' {
+                errors += 1;
+            }
+            errors <= max_errors
+        });
+        if matched {
+            return Some(insert);
+        }
+    }
+    None
+}
+
+pub struct Adapter {
+    pub name: String,
+    pub seq: Vec<u8>,
+    /// IUPAC masks of the sequence
+    masks: Vec<u8>,
+}
+impl Adapter {
+    pub fn new(name: &str, seq: &[u8]) -> Result<Self, std::io::Error> {
+        let seq = seq.to_ascii_uppercase();
+        if seq.is_empty() || seq.iter().any(|&b| dna::get_iupac_mask(b) == 0) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!(
+                    "invalid adapter '{}': only IUPAC nucleotide codes are allowed",
+                    name
+                ),
+            ));
+        }
+        Ok(Adapter {
+            name: name.to_string(),
+            masks: seq.iter().map(|&b| dna::get_iupac_mask(b)).collect(),
+            seq,
+        })
+    }
+}
+
+/// The built-in adapters of the sets, followed by the adapters in the FASTA file (if any).
+pub fn load_adapters(
+    kinds: &[AdapterKind],
+    adapter_fa: Option<&str>,
+) -> Result<Vec<Adapter>, std::io::Error> {
+    let mut adapters = Vec::new();
+    for &(kind, name, seq) in BUILTIN_ADAPTERS {
+        if kinds.contains(&kind) {
+            adapters.push(Adapter::new(name, seq.as_bytes())?);
+        }
+    }
+    if let Some(path) = adapter_fa {
+        let fa_iter = FaReader::new(path)?;
+        for record in fa_iter.records() {
+            let record = record
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e.to_string()))?;
+            adapters.push(Adapter::new(record.id(), record.seq())?);
+        }
+    }
+    Ok(adapters)
+}
+
+pub struct AdapterTrimmer {
+    pub adapters: Vec<Adapter>,
+    error_rate: f64,
+    min_overlap: usize,
+}
+impl AdapterTrimmer {
+    pub fn new(adapters: Vec<Adapter>, error_rate: f64, min_overlap: usize) -> Self {
+        AdapterTrimmer {
+            adapters,
+            error_rate,
+            min_overlap: min_overlap.max(1),
+        }
+    }
+    /// The leftmost adapter match in the read as (adapter index, start position).
+    /// On a tie, the adapter given first wins.
+    pub fn find(&self, seq: &[u8]) -> Option<(usize, usize)> {
+        let masks: Vec<u8> = seq.iter().map(|&b| dna::get_iupac_mask(b)).collect();
+        let mut best: Option<(usize, usize)> = None;
+        for (idx, adapter) in self.adapters.iter().enumerate() {
+            let limit = best.map_or(masks.len(), |(_, pos)| pos);
+            if let Some(pos) = self.find_adapter(&masks[..], &adapter.masks, limit) {
+                best = Some((idx, pos));
+            }
+        }
+        best
+    }
+    /// The length of the read after trimming the adapter (if any), which is recorded in `stats`.
+    fn trim(&self, seq: &[u8], stats: &mut TrimStats) -> usize {
+        match self.find(seq) {
+            Some((idx, pos)) => {
+                stats.add(Some(idx), seq.len() - pos);
+                pos
+            }
+            None => {
+                stats.add(None, 0);
+                seq.len()
+            }
+        }
+    }
+    /// The leftmost start (before `limit`) where the adapter (or its prefix at the 3' end) matches.
+    fn find_adapter(&self, masks: &[u8], adapter: &[u8], limit: usize) -> Option<usize> {
+        for pos in 0..limit.min(masks.len()) {
+            let overlap = adapter.len().min(masks.len() - pos);
+            if overlap < self.min_overlap {
+                break;
+            }
+            let max_errors = (overlap as f64 * self.error_rate) as usize;
+            let mut errors = 0;
+            let matched = masks[pos..pos + overlap]
+                .iter()
+                .zip(&adapter[..overlap])
+                .all(|(&r, &a)| {
+                    if r == 0 || r & !a != 0 {
+                        errors += 1;
+                    }
+                    errors <= max_errors
+                });
+            if matched {
+                return Some(pos);
+            }
+        }
+        None
+    }
+}
+
+/// Trimmed reads and bases per adapter.
+struct TrimStats {
+    total: usize,
+    discarded: usize,
+    reads: Vec<usize>,
+    bases: Vec<usize>,
+    /// Reads and bases trimmed at the insert boundary (paired-end mode only)
+    by_insert: Option<(usize, usize)>,
+    /// Reads and bases trimmed as homopolymer tails (with `--poly-x` only)
+    by_poly: Option<(usize, usize)>,
+}
+impl TrimStats {
+    fn new(n: usize) -> Self {
+        TrimStats {
+            total: 0,
+            discarded: 0,
+            reads: vec![0; n],
+            bases: vec![0; n],
+            by_insert: None,
+            by_poly: None,
+        }
+    }
+    /// The length of the (adapter-trimmed) read after trimming the homopolymer tail (if any).
+    fn trim_poly(&mut self, poly_tail: Option<&PolyTail>, seq: &[u8]) -> usize {
+        let Some(poly_tail) = poly_tail else {
+            return seq.len();
+        };
+        let end = poly_tail.tail_start(seq);
+        if let Some((reads, bases)) = self.by_poly.as_mut() {
+            if end < seq.len() {
+                *reads += 1;
+                *bases += seq.len() - end;
+            }
+        }
+        end
+    }
+    fn add(&mut self, adapter_idx: Option<usize>, trimmed_bases: usize) {
+        self.total += 1;
+        if let Some(idx) = adapter_idx {
+            self.reads[idx] += 1;
+            self.bases[idx] += trimmed_bases;
+        }
+    }
+    fn add_insert(&mut self, trimmed_bases: usize) {
+        self.total += 1;
+        if let Some((reads, bases)) = self.by_insert.as_mut() {
+            if trimmed_bases > 0 {
+                *reads += 1;
+                *bases += trimmed_bases;
+            }
+        }
+    }
+    fn report(&self, adapters: &[Adapter]) {
+        let trimmed = self.reads.iter().sum::<usize>() + self.by_insert.map_or(0, |(r, _)| r);
+        eprintln!(
+            "Reads: {}, trimmed: {}, discarded (too short): {}",
+            self.total, trimmed, self.discarded
+        );
+        eprintln!("#adapter\tsequence\treads\tbases");
+        for (i, adapter) in adapters.iter().enumerate() {
+            eprintln!(
+                "{}\t{}\t{}\t{}",
+                adapter.name,
+                String::from_utf8_lossy(&adapter.seq),
+                self.reads[i],
+                self.bases[i]
+            );
+        }
+        if let Some((reads, bases)) = self.by_insert {
+            eprintln!("insert_overlap\t-\t{}\t{}", reads, bases);
+        }
+        if let Some((reads, bases)) = self.by_poly {
+            eprintln!("poly_x\t-\t{}\t{}", reads, bases);
+        }
+    }
+}
+
+/// Samples the reads of FASTA/Q file and assembles the adapter from the overrepresented k-mers at the 3' ends.
+/// Outputs the adapter to [`std::io::stdout()`] in FASTA, which can be given to `trimadapter --adapter-fa`.
+///
+/// The most frequent k-mer (of at least 3 distinct bases) is extended to both sides while one base is dominant.
+/// Nothing is output if no k-mer occurs in `--min-ratio` of the sampled reads.
+///
+/// # Arguments
+///
+/// Check the arguments by `--help`
+///
+/// # Errors
+///
+/// Return an error if the operation cannot be completed.
+pub fn detect_adapter_fastx(
+    fx_path: &str,
+    dparas: &DetectAdapterArgs,
+    is_fasta: bool,
+) -> Result<(), std::io::Error> {
+    let sample_reads = dparas.sample_reads.unwrap_or(100000);
+    let tail = dparas.tail.unwrap_or(40);
+    let mut counter = KmerCounter::new(dparas.kmer_size.unwrap_or(12) as usize);
+    let mut n_reads = 0;
+    if is_fasta {
+        let fa_iter = FaReader::new(fx_path)?;
+        for record in fa_iter.records().take(sample_reads) {
+            match record {
+                Ok(read) => counter.add_tail(read.seq(), tail),
+                Err(e) => eprintln!("Error read FASTA: {}", e),
+            }
+            n_reads += 1;
+        }
+    } else {
+        let fq_iter = FqReader::new(fx_path)?;
+        for record in fq_iter.records().take(sample_reads) {
+            match record {
+                Ok(read) => counter.add_tail(read.seq(), tail),
+                Err(e) => eprintln!("Error read FASTQ: {}", e),
+            }
+            n_reads += 1;
+        }
+    }
+    let min_count = ((n_reads as f64 * dparas.min_ratio.unwrap_or(0.01)).ceil() as usize).max(2);
+    let Some((seq, seed_count)) = counter.assemble(min_count) else {
+        eprintln!("Warning: no adapter detected in {} reads", n_reads);
+        return Ok(());
+    };
+    let builtin = match_builtin(&seq, counter.k);
+    let mut writer = FxWriter::new(true);
+    match builtin {
+        Some((name, builtin_seq)) if dparas.match_builtin => writer.write(
+            name,
+            builtin_seq.as_bytes(),
+            Some(&format!("detected={}", String::from_utf8_lossy(&seq))),
+            &[],
+        )?,
+        _ => {
+            let mut desc = format!("seed_count={} reads={}", seed_count, n_reads);
+            if let Some((name, _)) = builtin {
+                desc.push_str(&format!(" builtin={}", name));
+            }
+            writer.write("detected_adapter", &seq, Some(&desc), &[])?
+        }
+    }
+    Ok(())
+}
+
+/// The built-in adapter (name, sequence) of which either sequence contains the first `k` bases of the other.
+/// If several adapters match (e.g., TruSeq read 1 and 2), the one sharing the longest prefix with `seq` is chosen.
+fn match_builtin(seq: &[u8], k: usize) -> Option<(&'static str, &'static str)> {
+    let contains = |a: &[u8], b: &[u8]| {
+        let b = &b[..k.min(b.len())];
+        a.windows(b.len()).any(|w| w == b)
+    };
+    let common_prefix = |a: &[u8]| a.iter().zip(seq).take_while(|(x, y)| x == y).count();
+    BUILTIN_ADAPTERS
+        .iter()
+        .filter(|(_, _, adapter)| {
+            contains(adapter.as_bytes(), seq) || contains(seq, adapter.as_bytes())
+        })
+        // `max_by_key` returns the last maximum, so reverse to prefer the adapter listed first
+        .rev()
+        .max_by_key(|(_, _, adapter)| common_prefix(adapter.as_bytes()))
+        .map(|&(_, name, adapter)| (name, adapter))
+}
+
+/// 2-bit encoded k-mer counts
+struct KmerCounter {
+    k: usize,
+    mask: u64,
+    counts: HashMap<u64, usize>,
+}
+impl KmerCounter {
+    fn new(k: usize) -> Self {
+        KmerCounter {
+            k,
+            mask: (1u64 << (2 * k)) - 1,
+            counts: HashMap::new(),
+        }
+    }
+    fn encode(b: u8) -> Option<u64> {
+        match b.to_ascii_uppercase() {
+            b'A' => Some(0),
+            b'C' => Some(1),
+            b'G' => Some(2),
+            b'T' => Some(3),
+            _ => None,
+        }
+    }
+    fn encode_seq(seq: &[u8]) -> u64 {
+        seq.iter()
+            .fold(0, |code, &b| code << 2 | Self::encode(b).unwrap_or(0))
+    }
+    fn decode(&self, code: u64) -> Vec<u8> {
+        (0..self.k)
+            .rev()
+            .map(|i| b"ACGT"[(code >> (2 * i) & 3) as usize])
+            .collect()
+    }
+    fn count(&self, code: u64) -> usize {
+        self.counts.get(&code).copied().unwrap_or(0)
+    }
+    /// Count the k-mers (of A, C, G and T only) in the last `tail` bases.
+    fn add_tail(&mut self, seq: &[u8], tail: usize) {
+        let (mut code, mut len) = (0u64, 0);
+        for &b in &seq[seq.len().saturating_sub(tail)..] {
+            match Self::encode(b) {
+                Some(c) => {
+                    code = (code << 2 | c) & self.mask;
+                    len += 1;
+                    if len >= self.k {
+                        *self.counts.entry(code).or_insert(0) += 1;
+                    }
+                }
+                None => len = 0,
+            }
+        }
+    }
+    /// The sequence assembled from the most frequent k-mer and the count of that k-mer.
+    fn assemble(&self, min_count: usize) -> Option<(Vec<u8>, usize)> {
+        let (&seed, &seed_count) = self
+            .counts
+            .iter()
+            .filter(|(&code, &count)| count >= min_count && self.is_complex(code))
+            .max_by_key(|(&code, &count)| (count, std::cmp::Reverse(code)))?;
+        let mut seq = self.decode(seed);
+        // the next base is dominant if its k-mer has >= 80% of the counts of the 4 k-mers
+        let pick = |codes: [u64; 4]| -> Option<u8> {
+            let counts = codes.map(|c| self.count(c));
+            let total: usize = counts.iter().sum();
+            let (best, &best_count) = counts.iter().enumerate().max_by_key(|(_, &c)| c)?;
+            (best_count >= min_count && best_count * 5 >= total * 4).then_some(b"ACGT"[best])
+        };
+        while seq.len() < MAX_DETECTED_LEN {
+            let suffix = Self::encode_seq(&seq[seq.len() + 1 - self.k..]);
+            match pick([0, 1, 2, 3].map(|b| (suffix << 2 | b) & self.mask)) {
+                Some(b) => seq.push(b),
+                None => break,
+            }
+        }
+        while seq.len() < MAX_DETECTED_LEN {
+            let prefix = Self::encode_seq(&seq[..self.k - 1]);
+            match pick([0, 1, 2, 3].map(|b| b << (2 * (self.k - 1)) | prefix)) {
+                Some(b) => seq.insert(0, b),
+                None => break,
+            }
+        }
+        Some((seq, seed_count))
+    }
+    /// Return true if the k-mer has at least 3 distinct bases (i.e., not like polyA or ACACAC...).
+    fn is_complex(&self, code: u64) -> bool {
+        let mut seen = [false; 4];
+        (0..self.k).for_each(|i| seen[(code >> (2 * i) & 3) as usize] = true);
+        seen.iter().filter(|&&s| s).count() >= 3
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_adapter() {
+        let trimmer = AdapterTrimmer::new(
+            load_adapters(&[AdapterKind::Truseq, AdapterKind::Nextera], None).unwrap(),
+            0.1,
+            3,
+        );
+        // full match of Nextera
+        assert_eq!(
+            trimmer.find(b"ACGTACGTACCTGTCTCTTATACACATCTCCGAGCCC"),
+            Some((2, 10))
+        );
+        // partial overlap at the 3' end, with one mismatch in 12 bases
+        assert_eq!(trimmer.find(b"TTTTTTTTTTAGATCGGTAGAG"), Some((0, 10)));
+        // two mismatches in 12 bases are too many
+        assert_eq!(trimmer.find(b"TTTTTTTTTTAGATCCGTAGAG"), None);
+        // shorter than the minimum overlap
+        assert_eq!(trimmer.find(b"TTTTTTTTTTAG"), None);
+        assert_eq!(trimmer.find(b"TTTTTTTTTAGA"), Some((0, 9)));
+        assert_eq!(trimmer.find(b""), None);
+        assert!(Adapter::new("bad", b"ACGT-A").is_err());
+    }
+
+    #[test]
+    fn test_find_insert() {
+        let insert = b"TTGCAGCATTCAGGCATCAGCTACCCATG";
+        let mut rc_insert = insert.to_vec();
+        dna::revcomp(&mut rc_insert);
+        let read1 = [&insert[..], b"AGATCGGAAGAGCACA"].concat();
+        let read2 = [&rc_insert[..], b"AGATCGGAAGAGCGTC"].concat();
+        assert_eq!(find_insert(&read1, &read2, 20, 0.1), Some(insert.len()));
+        // two adapter bases only
+        assert_eq!(
+            find_insert(&read1[..31], &read2[..31], 20, 0.1),
+            Some(insert.len())
+        );
+        // the insert is longer than the mates
+        assert_eq!(find_insert(&insert[..20], &rc_insert[..20], 10, 0.1), None);
+        // the overlap is too short
+        assert_eq!(find_insert(&read1, &read2, 30, 0.1), None);
+    }
+
+    #[test]
+    fn test_assemble_adapter() {
+        let adapter = b"AGATCGGAAGAGCACACGTCTGAACTCCAGTCA";
+        let inserts: [&[u8]; 4] = [
+            b"TTGCAGCATTCA",
+            b"GGCATCAGCTAC",
+            b"CCATGACTGAAT",
+            b"ACTCGTAGTTAG",
+        ];
+        let mut counter = KmerCounter::new(8);
+        for insert in inserts {
+            let read = [insert, &adapter[..20]].concat();
+            counter.add_tail(&read, 40);
+        }
+        counter.add_tail(b"AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA", 40);
+        let (seq, seed_count) = counter.assemble(2).unwrap();
+        assert_eq!(seq, adapter[..20].to_vec());
+        assert_eq!(seed_count, 4);
+        assert_eq!(
+            match_builtin(&seq, 8),
+            Some(("TruSeq_Read1", std::str::from_utf8(adapter).unwrap()))
+        );
+        assert!(counter.assemble(5).is_none());
+        assert_eq!(
+            match_builtin(b"AGATCGGAAGAGCGTCGTGTAG", 12).map(|(name, _)| name),
+            Some("TruSeq_Read2")
+        );
+    }
+}
@@ -0,0 +1,148 @@
+use crate::io_utils::buffer_reader_maybe_gz;
+use std::collections::HashMap;
+use std::io::BufRead;
+
+/// Aliases of sequence names, e.g., to match a BED using `chr1` with an Ensembl FASTA using `1`.
+///
+/// * A two-column table (`name alias`, tab- or space-delimited) maps names in both directions.
+/// * With `auto_chr`, names with and without the `chr` prefix are matched, as well as `chrM` and `MT`.
+#[derive(Debug, Default)]
+pub struct NameAlias {
+    table: HashMap<String, String>,   // {1st column: 2nd column}
+    reverse: HashMap<String, String>, // {2nd column: 1st column}
+    auto_chr: bool,
+}
+impl NameAlias {
+    /// Load the alias table (if any). Empty lines and `#` lines are skipped.
+    ///
+    /// # Errors
+    ///
+    /// Return an error with the file name and the line number if a line has fewer than two columns.
+    pub fn from(path: Option<&str>, auto_chr: bool) -> Result<Self, std::io::Error> {
+        let mut alias = NameAlias {
+            auto_chr,
+            ..Default::default()
+        };
+        let Some(path) = path else {
+            return Ok(alias);
+        };
+        let reader = buffer_reader_maybe_gz(path)?;
+        for (i, line) in reader.lines().enumerate() {
+            let line = line?;
+            let columns: Vec<&str> = line.split_whitespace().collect();
+            match columns[..] {
+                [] => {}
+                [first, ..] if first.starts_with('#') => {}
+                [name, alias_name, ..] => {
+                    alias.table.insert(name.to_string(), alias_name.to_string());
+                    alias
+                        .reverse
+                        .insert(alias_name.to_string(), name.to_string());
+                }
+                _ => {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        format!("{}:{}: expected 2 columns (name alias)", path, i + 1),
+                    ))
+                }
+            }
+        }
+        Ok(alias)
+    }
+    /// Other names of `name` to try in order: the table (both directions), then the `chr` variants.
+    pub fn candidates(&self, name: &str) -> Vec<String> {
+        let mut names: Vec<String> = Vec::new();
+        names.extend(self.table.get(name).cloned());
+        names.extend(self.reverse.get(name).cloned());
+        if self.auto_chr {
+            names.push(to_ucsc(name));
+            names.push(to_ensembl(name));
+        }
+        names.retain(|n| n != name);
+        names
+    }
+    /// The new name of `name` by the table (1st column to 2nd column) and then by the `chr` conversion.
+    pub fn rename(&self, name: &str, chr_style: Option<ChrStyle>) -> String {
+        let name = self.table.get(name).map_or(name, |n| n.as_str());
+        match chr_style {
+            Some(ChrStyle::Ucsc) => to_ucsc(name),
+            Some(ChrStyle::Ensembl) => to_ensembl(name),
+            None => name.to_string(),
+        }
+    }
+}
+
+/// Naming style of chromosomes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChrStyle {
+    /// `chr1`, `chrM`
+    Ucsc,
+    /// `1`, `MT`
+    Ensembl,
+}
+
+/// `1` to `chr1` (`1`-`22`, `X` and `Y`) and `MT` to `chrM`.
+/// Other names (e.g., `chr1`, `scaffold_1` and `GL000220.1`) are kept.
+fn to_ucsc(name: &str) -> String {
+    let is_primary = matches!(name, "X" | "Y")
+        || (name.parse::<u8>().is_ok_and(|n| (1..=22).contains(&n)) && !name.starts_with('0'));
+    match name {
+        "MT" => "chrM".to_string(),
+        _ if is_primary => format!("chr{}", name),
+        _ => name.to_string(),
+    }
+}
+/// `chr1` to `1` and `chrM` to `MT`. Names without the `chr` prefix are kept.
+fn to_ensembl(name: &str) -> String {
+    match name.strip_prefix("chr") {
+        Some("M") => "MT".to_string(),
+        Some(stripped) if !stripped.is_empty() => stripped.to_string(),
+        _ => name.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chr_style() {
+        assert_eq!(to_ucsc("1"), "chr1");
+        assert_eq!(to_ucsc("MT"), "chrM");
+        assert_eq!(to_ucsc("chrX"), "chrX");
+        assert_eq!(to_ucsc("22"), "chr22");
+        assert_eq!(to_ucsc("Y"), "chrY");
+        // not primary chromosomes
+        assert_eq!(to_ucsc("scaffold_1"), "scaffold_1");
+        assert_eq!(to_ucsc("GL000220.1"), "GL000220.1");
+        assert_eq!(to_ucsc("KI270728.1"), "KI270728.1");
+        assert_eq!(to_ucsc("23"), "23");
+        assert_eq!(to_ucsc("01"), "01");
+        assert_eq!(to_ensembl("chr1"), "1");
+        assert_eq!(to_ensembl("chrM"), "MT");
+        assert_eq!(to_ensembl("scaffold_1"), "scaffold_1");
+        assert_eq!(to_ensembl("chr"), "chr");
+    }
+
+    #[test]
+    fn test_candidates() {
+        let mut alias = NameAlias::from(None, true).unwrap();
+        assert_eq!(alias.candidates("chr1"), vec!["1".to_string()]);
+        assert_eq!(alias.candidates("MT"), vec!["chrM".to_string()]);
+        alias
+            .table
+            .insert("NC_000001.11".to_string(), "chr1".to_string());
+        alias
+            .reverse
+            .insert("chr1".to_string(), "NC_000001.11".to_string());
+        assert_eq!(
+            alias.candidates("chr1"),
+            vec!["NC_000001.11".to_string(), "1".to_string()]
+        );
+        assert_eq!(
+            alias.rename("NC_000001.11", Some(ChrStyle::Ensembl)),
+            "1".to_string()
+        );
+        assert_eq!(alias.rename("2", None), "2".to_string());
+    }
+}
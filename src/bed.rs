@@ -1,93 +1,489 @@
+use crate::alias::NameAlias;
+use crate::gff;
 use crate::io_utils::buffer_reader_maybe_gz;
+use crate::rmsk;
 use rayon::prelude::*;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::io::BufRead;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub struct BedPos(pub usize, pub usize); // (start, end)
 pub struct BedMap {
     map: HashMap<String, Vec<BedPos>>, // {rname: vec[Bedpos..]}
+    ends: MaxEnds,
+    alias: NameAlias,
 }
 impl BedMap {
     pub fn new() -> Self {
         Self {
             map: HashMap::new(),
+            ends: HashMap::new(),
+            alias: NameAlias::default(),
         }
     }
+    /// Look up the sequence names by the aliases as well (e.g., `1` for `chr1`).
+    pub fn with_alias(mut self, alias: NameAlias) -> Self {
+        self.alias = alias;
+        self
+    }
     pub fn add(&mut self, key: String, region: BedPos) {
         self.map.entry(key).or_default().push(region);
     }
+    /// Same as [`BedMap::add`], keeping the largest end with its line number in the file for [`BedMap::max_end`].
+    fn add_from_line(&mut self, key: String, region: BedPos, origin: BedOrigin) {
+        update_max_end(&mut self.ends, &key, origin);
+        self.add(key, region);
+    }
     pub fn get(&self, key: &str) -> Option<&Vec<BedPos>> {
-        self.map.get(key)
+        lookup(&self.map, &self.alias, key).map(|(_, v)| v)
     }
+    /// Load the BED file and merge overlapping intervals. Columns other than the first three (e.g., strand) are ignored.
     pub fn from(path: &str) -> Result<BedMap, std::io::Error> {
-        let reader = buffer_reader_maybe_gz(path)?;
+        Self::from_with(path, &RegionOptions::default())
+    }
+    /// Same as [`BedMap::from`], with the options.
+    /// With `blocks`, only the blocks (e.g., exons) of BED12 features are loaded, and features without blocks are loaded as a whole.
+    pub fn from_with(path: &str, options: &RegionOptions) -> Result<BedMap, std::io::Error> {
         let mut bed_map: BedMap = BedMap::new();
-        for line in reader.lines() {
-            match line {
-                Ok(line_content) => {
-                    let columns: Vec<&str> = line_content.split('\t').collect();
-                    if columns.len() > 2 {
-                        let name = columns[0].to_string();
-                        if let (Ok(start), Ok(end)) =
-                            (columns[1].parse::<usize>(), columns[2].parse::<usize>())
-                        {
-                            bed_map.add(name, BedPos(start, end));
-                        } else {
-                            eprintln!("Error parsing start or end for line: {}", line_content);
-                        }
-                    }
-                }
-                Err(e) => eprintln!("Error reading line: {}", e),
+        read_bed(path, options, |chrom, feature, origin| {
+            match feature.blocks {
+                Some(blocks) if options.blocks => blocks
+                    .into_iter()
+                    .for_each(|block| bed_map.add_from_line(chrom.clone(), block, origin)),
+                _ => bed_map.add_from_line(chrom, feature.pos, origin),
             }
-        }
+        })?;
         bed_map.merge();
         Ok(bed_map)
     }
-    /// Merge overlapping intervals in the map and return a new HashMap
+    /// The BED name matched by `key` (directly or by an alias), the largest end of its intervals
+    /// (before the padding) and its line number.
+    pub fn max_end(&self, key: &str) -> Option<(&String, usize, usize)> {
+        lookup(&self.ends, &self.alias, key).map(|(name, o)| (name, o.end, o.line))
+    }
+    /// Iterate over `(rname, merged intervals)` in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &Vec<BedPos>)> {
+        self.map.iter()
+    }
+    /// Merge overlapping (and book-ended) intervals of each sequence in place.
     fn merge(&mut self) {
-        let merged_map: HashMap<String, Vec<BedPos>> = self
-            .map
-            .par_iter()
-            .map(|(name, intervals)| {
-                let mut intervals: Vec<BedPos> = intervals.to_vec();
-                if intervals.len() <= 1 {
-                    return (name.clone(), intervals);
-                }
-                // Sort by start position
-                intervals.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+        self.map
+            .par_iter_mut()
+            .for_each(|(_, intervals)| *intervals = merge_intervals(std::mem::take(intervals)));
+    }
+}
 
-                let mut merged_intervals = Vec::new();
-                for i in 1..intervals.len() {
-                    if intervals[i - 1].1 < intervals[i].0 {
-                        merged_intervals.push(intervals[i - 1]);
-                    } else {
-                        intervals[i].0 = intervals[i - 1].0;
-                    }
-                }
-                merged_intervals.push(intervals[intervals.len() - 1]);
-                (name.clone(), merged_intervals)
-            })
+/// Sort the intervals and merge overlapping or book-ended ones (e.g., `[1,5)` and `[5,8)` become `[1,8)`).
+pub fn merge_intervals(mut intervals: Vec<BedPos>) -> Vec<BedPos> {
+    intervals.sort_unstable();
+    let mut merged: Vec<BedPos> = Vec::with_capacity(intervals.len());
+    for pos in intervals {
+        match merged.last_mut() {
+            Some(last) if pos.0 <= last.1 => last.1 = last.1.max(pos.1),
+            _ => merged.push(pos),
+        }
+    }
+    merged
+}
+/// Return the regions covered by both `a` and `b`. Both must be merged (sorted and non-overlapping).
+pub fn intersect(a: &[BedPos], b: &[BedPos]) -> Vec<BedPos> {
+    let mut result = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        let start = a[i].0.max(b[j].0);
+        let end = a[i].1.min(b[j].1);
+        if start < end {
+            result.push(BedPos(start, end));
+        }
+        if a[i].1 < b[j].1 {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    result
+}
+/// Return the regions of `a` not covered by `b`. Both must be merged (sorted and non-overlapping).
+pub fn subtract(a: &[BedPos], b: &[BedPos]) -> Vec<BedPos> {
+    let mut result = Vec::new();
+    let mut j = 0;
+    for &BedPos(start, end) in a {
+        while j < b.len() && b[j].1 <= start {
+            j += 1;
+        }
+        let mut cur = start;
+        for other in b[j..].iter().take_while(|other| other.0 < end) {
+            if cur < other.0 {
+                result.push(BedPos(cur, other.0));
+            }
+            cur = cur.max(other.1);
+        }
+        if cur < end {
+            result.push(BedPos(cur, end));
+        }
+    }
+    result
+}
+/// Return the regions of `[0, len)` not covered by `a`, which must be merged (sorted and non-overlapping).
+pub fn complement(a: &[BedPos], len: usize) -> Vec<BedPos> {
+    subtract(&[BedPos(0, len)], a)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strand {
+    Forward,
+    Reverse,
+    Unknown,
+}
+impl Strand {
+    pub fn from(s: &str) -> Self {
+        match s {
+            "+" => Strand::Forward,
+            "-" => Strand::Reverse,
+            _ => Strand::Unknown,
+        }
+    }
+}
+
+/// One BED line with the optional BED6 columns (name, score and strand) and BED12 blocks. `.` is treated as absent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BedFeature {
+    pub pos: BedPos,
+    pub name: Option<String>,
+    pub score: Option<String>,
+    pub strand: Strand,
+    /// Blocks (e.g., exons) of BED12 in absolute positions, sorted by start
+    pub blocks: Option<Vec<BedPos>>,
+}
+/// BED features grouped by the sequence name. The features are kept as they are (in file order, without merging).
+pub struct BedFeatureMap {
+    map: HashMap<String, Vec<BedFeature>>, // {rname: vec[BedFeature..]}
+    ends: MaxEnds,
+    alias: NameAlias,
+}
+impl BedFeatureMap {
+    pub fn new() -> Self {
+        Self {
+            map: HashMap::new(),
+            ends: HashMap::new(),
+            alias: NameAlias::default(),
+        }
+    }
+    /// Look up the sequence names by the aliases as well (e.g., `1` for `chr1`).
+    pub fn with_alias(mut self, alias: NameAlias) -> Self {
+        self.alias = alias;
+        self
+    }
+    /// Add a feature read from the file at `origin`.
+    pub fn add(&mut self, key: String, feature: BedFeature, origin: BedOrigin) {
+        update_max_end(&mut self.ends, &key, origin);
+        self.map.entry(key).or_default().push(feature);
+    }
+    pub fn get(&self, key: &str) -> Option<&Vec<BedFeature>> {
+        lookup(&self.map, &self.alias, key).map(|(_, v)| v)
+    }
+    /// The BED name matched by `key` (directly or by an alias), the largest end of its features
+    /// (before the padding) and its line number.
+    pub fn max_end(&self, key: &str) -> Option<(&String, usize, usize)> {
+        lookup(&self.ends, &self.alias, key).map(|(name, o)| (name, o.end, o.line))
+    }
+    /// Iterate over `(rname, features)` in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &Vec<BedFeature>)> {
+        self.map.iter()
+    }
+    pub fn from(path: &str) -> Result<BedFeatureMap, std::io::Error> {
+        Self::from_with(path, &RegionOptions::default())
+    }
+    /// Same as [`BedFeatureMap::from`], with the options (`blocks` is not used).
+    pub fn from_with(path: &str, options: &RegionOptions) -> Result<BedFeatureMap, std::io::Error> {
+        let mut feature_map = BedFeatureMap::new();
+        read_bed(path, options, |chrom, feature, origin| {
+            feature_map.add(chrom, feature, origin)
+        })?;
+        Ok(feature_map)
+    }
+}
+
+/// The line number of a feature in the file and its end as written there, i.e., before the padding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BedOrigin {
+    pub line: usize,
+    pub end: usize,
+}
+
+/// The feature with the largest end of each sequence name (the first one on a tie).
+type MaxEnds = HashMap<String, BedOrigin>;
+fn update_max_end(ends: &mut MaxEnds, key: &str, origin: BedOrigin) {
+    match ends.get_mut(key) {
+        Some(max_end) if max_end.end < origin.end => *max_end = origin,
+        Some(_) => {}
+        None => {
+            ends.insert(key.to_string(), origin);
+        }
+    }
+}
+
+/// Look up `key` in the map, and then its aliases. Return the matched name and the value.
+fn lookup<'a, T>(
+    map: &'a HashMap<String, T>,
+    alias: &NameAlias,
+    key: &str,
+) -> Option<(&'a String, &'a T)> {
+    map.get_key_value(key).or_else(|| {
+        alias
+            .candidates(key)
+            .iter()
+            .find_map(|name| map.get_key_value(name.as_str()))
+    })
+}
+
+/// Check of the sequence names of a BED file against the input records.
+/// The BED names which match no input record, and the intervals which extend beyond the record,
+/// are warned about, or are an error in the strict mode.
+pub struct BedNameCheck {
+    path: String,
+    strict: bool,
+    matched: HashSet<String>,
+}
+impl BedNameCheck {
+    pub fn new(path: &str, strict: bool) -> Self {
+        Self {
+            path: path.to_string(),
+            strict,
+            matched: HashSet::new(),
+        }
+    }
+    /// Check the record `id` of length `len`, where `max_end` is the matched BED name,
+    /// the largest end of its intervals and its line number.
+    pub fn check(
+        &mut self,
+        id: &str,
+        max_end: Option<(&String, usize, usize)>,
+        len: usize,
+    ) -> Result<(), std::io::Error> {
+        let Some((name, max_end, line)) = max_end else {
+            return Ok(());
+        };
+        self.matched.insert(name.clone());
+        if max_end > len {
+            let message = format!(
+                "{}:{}: interval end {} is out of range for '{}' (length {})",
+                self.path, line, max_end, id, len
+            );
+            if self.strict {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    message,
+                ));
+            }
+            eprintln!("Warning: {}", message);
+        }
+        Ok(())
+    }
+    /// Warn about (or return an error in the strict mode) the sequence names of the BED (`names`)
+    /// which did not match any input record.
+    pub fn finish<'a>(
+        &self,
+        names: impl Iterator<Item = &'a String>,
+    ) -> Result<(), std::io::Error> {
+        let mut missing: Vec<&str> = names
+            .filter(|&name| !self.matched.contains(name))
+            .map(|name| name.as_str())
             .collect();
-        self.map = merged_map;
+        if missing.is_empty() {
+            return Ok(());
+        }
+        missing.sort_unstable();
+        let message = format!(
+            "{}: sequence names not found in the input: {}",
+            self.path,
+            missing.join(", ")
+        );
+        if self.strict {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                message,
+            ));
+        }
+        eprintln!("Warning: {}", message);
+        Ok(())
     }
 }
 
-pub fn is_overlapping(pos: usize, bed_pos: &[BedPos]) -> (bool, usize) {
-    // (is_overlap, end_idx)
-    if bed_pos.is_empty() {
-        (false, 0)
-    } else {
-        for (idx, &range) in bed_pos.iter().enumerate() {
-            if range.1 < pos {
-            } else if pos < range.1 && range.0 <= pos {
-                return (true, idx); // If there's an overlap, return the index of the interval
-            } else {
-                return (false, idx);
+/// Options to load regions from a BED, GFF3/GTF or RepeatMasker `.out` file.
+#[derive(Debug, Default, Clone)]
+pub struct RegionOptions {
+    /// Load only the blocks (e.g., exons) of BED12 features
+    pub blocks: bool,
+    /// Load only the RepeatMasker repeats of these classes/families (e.g., `LINE/L1`, `LINE` or `Simple_repeat`). All if empty
+    pub repeat_classes: Vec<String>,
+    /// Extend each region (and block) by this number of bases on both sides
+    pub padding: usize,
+}
+
+/// Read BED (BED3 to BED12) lines and pass `(chrom, feature, origin)` to `add` in file order.
+/// GFF3/GTF and RepeatMasker `.out` files (by the file extension) are read as well, with the positions converted to 0-based.
+/// The repeat classes and the padding of `options` are applied here.
+///
+/// Columns are separated by tabs, or by whitespace if the line has no tab.
+/// Empty lines and header lines (`track`, `browser` and `#`) are skipped.
+///
+/// # Errors
+///
+/// Return an error with the file name and the line number if a line is malformed
+/// (e.g., fewer than three columns, a non-numeric or negative coordinate, `start > end` or inconsistent BED12 blocks).
+fn read_bed<F: FnMut(String, BedFeature, BedOrigin)>(
+    path: &str,
+    options: &RegionOptions,
+    mut add: F,
+) -> Result<(), std::io::Error> {
+    let mut add = |chrom: String, mut feature: BedFeature, line: usize| {
+        let origin = BedOrigin {
+            line,
+            end: feature.pos.1,
+        };
+        if options.padding > 0 {
+            let pad = |pos: &mut BedPos| {
+                *pos = BedPos(
+                    pos.0.saturating_sub(options.padding),
+                    pos.1.saturating_add(options.padding),
+                )
+            };
+            pad(&mut feature.pos);
+            feature.blocks.iter_mut().flatten().for_each(pad);
+        }
+        add(chrom, feature, origin)
+    };
+    if rmsk::is_rmsk_path(path) {
+        return rmsk::read_rmsk(path, |record, line| {
+            if options.repeat_classes.is_empty() || record.is_in_classes(&options.repeat_classes) {
+                add(record.seqid.clone(), record.to_bed_feature(), line)
             }
+        });
+    }
+    if !options.repeat_classes.is_empty() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("{}: repeat classes require a RepeatMasker .out file", path),
+        ));
+    }
+    if gff::is_gff_path(path) {
+        return gff::read_gff(path, |feature, line| {
+            add(feature.seqid.clone(), feature.to_bed_feature(), line)
+        });
+    }
+    let reader = buffer_reader_maybe_gz(path)?;
+    for (i, line) in reader.lines().enumerate() {
+        match line {
+            Ok(line_content) => match parse_bed_line(&line_content) {
+                Ok(Some((chrom, feature))) => add(chrom, feature, i + 1),
+                Ok(None) => {}
+                Err(e) => {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        format!("{}:{}: {}", path, i + 1, e),
+                    ))
+                }
+            },
+            Err(e) => eprintln!("Error reading line: {}", e),
+        }
+    }
+    Ok(())
+}
+/// Parse a BED line. Return `Ok(None)` for an empty or header line, and `Err(message)` for a malformed line.
+fn parse_bed_line(line_content: &str) -> Result<Option<(String, BedFeature)>, String> {
+    let line_content = line_content.trim_end_matches('\r');
+    let columns: Vec<&str> = if line_content.contains('\t') {
+        line_content.split('\t').collect()
+    } else {
+        line_content.split_whitespace().collect()
+    };
+    match columns.first() {
+        None | Some(&"track") | Some(&"browser") | Some(&"") => return Ok(None),
+        Some(c) if c.starts_with('#') => return Ok(None),
+        _ => {}
+    }
+    if columns.len() < 3 {
+        return Err(format!(
+            "expected at least 3 columns, found {}",
+            columns.len()
+        ));
+    }
+    let parse_coord = |name: &str, v: &str| -> Result<usize, String> {
+        v.parse::<usize>().map_err(|_| match v.parse::<i64>() {
+            Ok(_) => format!("{} {} is out of range (must be >= 0)", name, v),
+            Err(_) => format!("invalid {} '{}'", name, v),
+        })
+    };
+    let start = parse_coord("start", columns[1])?;
+    let end = parse_coord("end", columns[2])?;
+    if start > end {
+        return Err(format!("start {} is greater than end {}", start, end));
+    }
+    let optional = |i: usize| {
+        columns
+            .get(i)
+            .filter(|&&v| !v.is_empty() && v != ".")
+            .map(|v| v.to_string())
+    };
+    let blocks = if columns.len() >= 12 {
+        match parse_blocks(start, end, columns[9], columns[10], columns[11]) {
+            Some(blocks) => Some(blocks),
+            None => return Err("invalid BED12 blocks".to_string()),
         }
-        (false, bed_pos.len())
+    } else {
+        None
+    };
+    let feature = BedFeature {
+        pos: BedPos(start, end),
+        name: optional(3),
+        score: optional(4),
+        strand: columns.get(5).map_or(Strand::Unknown, |&s| Strand::from(s)),
+        blocks,
+    };
+    Ok(Some((columns[0].to_string(), feature)))
+}
+/// Parse `blockCount`, `blockSizes` and `blockStarts` (relative to `start`) to absolute blocks.
+/// Return `None` if the columns are inconsistent or a block is outside `[start, end)`.
+fn parse_blocks(
+    start: usize,
+    end: usize,
+    count: &str,
+    sizes: &str,
+    starts: &str,
+) -> Option<Vec<BedPos>> {
+    let count = count.parse::<usize>().ok()?;
+    let parse_list = |s: &str| -> Option<Vec<usize>> {
+        s.trim_end_matches(',')
+            .split(',')
+            .map(|v| v.trim().parse::<usize>().ok())
+            .collect()
+    };
+    let sizes = parse_list(sizes)?;
+    let starts = parse_list(starts)?;
+    if sizes.len() != count || starts.len() != count {
+        return None;
+    }
+    let mut blocks: Vec<BedPos> = starts
+        .iter()
+        .zip(sizes.iter())
+        .map(|(&s, &size)| BedPos(start + s, start + s + size))
+        .collect();
+    if blocks.iter().any(|b| b.1 > end) {
+        return None;
     }
+    blocks.sort_unstable();
+    Some(blocks)
+}
+
+/// Return `(is_overlap, idx)`, where `idx` is the index of the first interval which does not end before `pos`.
+/// `bed_pos` must be merged (sorted and non-overlapping), so that the interval is found by binary search.
+pub fn is_overlapping(pos: usize, bed_pos: &[BedPos]) -> (bool, usize) {
+    let idx = bed_pos.partition_point(|range| range.1 < pos);
+    let is_overlap = bed_pos
+        .get(idx)
+        .is_some_and(|range| range.0 <= pos && pos < range.1);
+    (is_overlap, idx)
 }
 
 #[cfg(test)]
@@ -100,6 +496,10 @@ mod tests {
         for [start, end] in [[1, 5], [6, 10], [2, 7]] {
             bed_map.add("id_1".to_string(), BedPos(start, end));
         }
+        // contained and book-ended intervals
+        for [start, end] in [[1, 10], [2, 5], [10, 12], [20, 30], [21, 22]] {
+            bed_map.add("id_3".to_string(), BedPos(start, end));
+        }
         for [start, end] in [[13, 21], [31, 35], [19, 23], [6, 10], [30, 32], [41, 45]] {
             bed_map.add("id_2".to_string(), BedPos(start, end));
         }
@@ -114,6 +514,147 @@ mod tests {
             BedPos(41, 45),
         ];
         assert_eq!(*id_2_bed, result);
+        let id_3_bed = bed_map.get("id_3").unwrap();
+        assert_eq!(*id_3_bed, vec![BedPos(1, 12), BedPos(20, 30)]);
+    }
+    #[test]
+    fn test_set_operations() {
+        let a = vec![BedPos(0, 10), BedPos(20, 30), BedPos(40, 50)];
+        let b = vec![BedPos(5, 25), BedPos(28, 29), BedPos(45, 60)];
+        assert_eq!(
+            intersect(&a, &b),
+            vec![
+                BedPos(5, 10),
+                BedPos(20, 25),
+                BedPos(28, 29),
+                BedPos(45, 50)
+            ]
+        );
+        assert_eq!(
+            subtract(&a, &b),
+            vec![BedPos(0, 5), BedPos(25, 28), BedPos(29, 30), BedPos(40, 45)]
+        );
+        assert_eq!(subtract(&a, &[]), a);
+        assert_eq!(intersect(&a, &[]), vec![]);
+        assert_eq!(
+            complement(&a, 55),
+            vec![BedPos(10, 20), BedPos(30, 40), BedPos(50, 55)]
+        );
+        assert_eq!(complement(&[], 5), vec![BedPos(0, 5)]);
+    }
+    #[test]
+    fn test_parse_bed_line() {
+        let (chrom, feature) = parse_bed_line("chr1\t5\t10").unwrap().unwrap();
+        assert_eq!(chrom, "chr1");
+        assert_eq!(
+            feature,
+            BedFeature {
+                pos: BedPos(5, 10),
+                name: None,
+                score: None,
+                strand: Strand::Unknown,
+                blocks: None,
+            }
+        );
+        let (_, feature) = parse_bed_line("chr1\t5\t10\tgene1\t.\t-").unwrap().unwrap();
+        assert_eq!(
+            feature,
+            BedFeature {
+                pos: BedPos(5, 10),
+                name: Some("gene1".to_string()),
+                score: None,
+                strand: Strand::Reverse,
+                blocks: None,
+            }
+        );
+        let (_, feature) =
+            parse_bed_line("chr1\t100\t200\ttx1\t0\t+\t100\t200\t0\t2\t10,20,\t0,80,")
+                .unwrap()
+                .unwrap();
+        assert_eq!(
+            feature.blocks,
+            Some(vec![BedPos(100, 110), BedPos(180, 200)])
+        );
+        // block outside the feature
+        assert!(
+            parse_bed_line("chr1\t100\t200\ttx1\t0\t+\t100\t200\t0\t2\t10,30,\t0,80,").is_err()
+        );
+        // blockCount does not match
+        assert!(
+            parse_bed_line("chr1\t100\t200\ttx1\t0\t+\t100\t200\t0\t3\t10,20,\t0,80,").is_err()
+        );
+        assert!(parse_bed_line("chr1\t5").is_err());
+        assert!(parse_bed_line("chr1\tfive\t10").is_err());
+        assert!(parse_bed_line("chr1\t10\t5").is_err());
+        assert!(parse_bed_line("chr1\t-1\t5").is_err());
+    }
+    #[test]
+    fn test_parse_bed_header_and_whitespace() {
+        for line in [
+            "",
+            "track name=test",
+            "browser position chr1:1-100",
+            "#chrom\tstart",
+        ] {
+            assert_eq!(parse_bed_line(line), Ok(None));
+        }
+        let (chrom, feature) = parse_bed_line("chr1  5 10 gene 1 +\r").unwrap().unwrap();
+        assert_eq!(chrom, "chr1");
+        assert_eq!(feature.pos, BedPos(5, 10));
+        assert_eq!(feature.strand, Strand::Forward);
+        // a name with a space in a tab-delimited line
+        let (_, feature) = parse_bed_line("chr1\t5\t10\tgene 1").unwrap().unwrap();
+        assert_eq!(feature.name, Some("gene 1".to_string()));
+    }
+    #[test]
+    fn test_bed_name_check() {
+        let names = ["chr1".to_string(), "chr2".to_string(), "chr3".to_string()];
+        let mut check = BedNameCheck::new("a.bed", true);
+        check.check("chr1", Some((&names[0], 10, 1)), 10).unwrap();
+        check.check("chr4", None, 10).unwrap();
+        let err = check
+            .check("chr2", Some((&names[1], 11, 3)), 10)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "a.bed:3: interval end 11 is out of range for 'chr2' (length 10)"
+        );
+        let err = check.finish(names.iter()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "a.bed: sequence names not found in the input: chr3"
+        );
+        // matched by an alias
+        check.check("2", Some((&names[1], 5, 2)), 10).unwrap();
+        check.check("chr3", Some((&names[2], 5, 4)), 10).unwrap();
+        assert!(check.finish(names.iter()).is_ok());
+        // only warned without the strict mode
+        let mut check = BedNameCheck::new("a.bed", false);
+        check.check("chr1", Some((&names[0], 11, 1)), 10).unwrap();
+        assert!(check.finish(names.iter()).is_ok());
+    }
+    #[test]
+    fn test_lookup_alias() {
+        let mut bed_map = BedMap::new();
+        bed_map.add_from_line(
+            "chr1".to_string(),
+            BedPos(0, 5),
+            BedOrigin { line: 1, end: 5 },
+        );
+        bed_map.add_from_line(
+            "chr1".to_string(),
+            BedPos(2, 4),
+            BedOrigin { line: 2, end: 4 },
+        );
+        bed_map.add("chrM".to_string(), BedPos(1, 5));
+        bed_map.add("1".to_string(), BedPos(2, 5));
+        assert_eq!(bed_map.get("MT"), None);
+        let bed_map = bed_map.with_alias(NameAlias::from(None, true).unwrap());
+        assert_eq!(bed_map.get("MT"), Some(&vec![BedPos(1, 5)]));
+        // the exact name comes first
+        assert_eq!(bed_map.get("1"), Some(&vec![BedPos(2, 5)]));
+        assert_eq!(bed_map.max_end("chr1"), Some((&"chr1".to_string(), 5, 1)));
+        assert_eq!(bed_map.get("2"), None);
     }
     #[test]
     fn test_is_overlapping() {
@@ -129,5 +670,9 @@ mod tests {
         assert_eq!(result, (true, 1));
         let result = is_overlapping(32, &bed_pos[2..]);
         assert_eq!(result, (true, 0));
+        // the end is exclusive
+        assert_eq!(is_overlapping(10, &bed_pos), (false, 0));
+        assert_eq!(is_overlapping(50, &bed_pos), (false, 4));
+        assert_eq!(is_overlapping(0, &[]), (false, 0));
     }
 }
@@ -0,0 +1,201 @@
+use crate::bed::{self, BedFeature, BedFeatureMap, BedMap, BedPos, RegionOptions, Strand};
+use crate::faidx::FaIndex;
+use crate::io_utils::Output;
+use crate::sub_cli::{BedArgs, BedOperation};
+use std::collections::HashMap;
+
+/// Performs an interval operation on a BED (0-based, half-open) file.
+/// Outputs the results to [`std::io::stdout()`] in BED format.
+///
+/// * `merge`, `intersect`, `subtract` and `complement` treat the intervals as sets.
+///   The inputs are merged first, and the output (BED3) is sorted by the sequence name and position.
+///   With `--genome`, the sequences are output in the order of the genome.
+/// * `slop` and `flank` work on each feature and keep the name, score and strand columns.
+///   With `--genome`, the results are clipped to the sequence lengths.
+///
+/// # Arguments
+///
+/// Check the arguments by `--help`
+///
+/// # Errors
+///
+/// Return an error if `complement` has no `--genome`, or the operation cannot be completed.
+pub fn bed_ops(bparas: &BedArgs) -> Result<(), std::io::Error> {
+    let genome = match &bparas.genome {
+        Some(path) => Some(load_genome(path)?),
+        None => None,
+    };
+    let options = RegionOptions {
+        repeat_classes: bparas.repeat_class.clone(),
+        ..Default::default()
+    };
+    let mut output = Output::new();
+    match bparas.operation {
+        BedOperation::Merge
+        | BedOperation::Intersect
+        | BedOperation::Subtract
+        | BedOperation::Complement => {
+            let bed_map = BedMap::from_with(&bparas.in_bed, &options)?;
+            let other_map = match &bparas.other_bed {
+                Some(path) => BedMap::from(path)?,
+                None => BedMap::new(),
+            };
+            let names = match (&genome, bparas.operation) {
+                (Some(index), BedOperation::Complement) => {
+                    for (name, _) in bed_map.iter() {
+                        if index.get(name).is_none() {
+                            eprintln!("Warning: '{}' is not in the genome, skipped", name);
+                        }
+                    }
+                    index.records().iter().map(|r| r.name.clone()).collect()
+                }
+                _ => sorted_names(bed_map.iter().map(|(name, _)| name), genome.as_ref()),
+            };
+            for name in names {
+                let intervals = bed_map.get(&name).map_or(&[][..], |v| v.as_slice());
+                let others = other_map.get(&name).map_or(&[][..], |v| v.as_slice());
+                let result = match bparas.operation {
+                    BedOperation::Intersect => bed::intersect(intervals, others),
+                    BedOperation::Subtract => bed::subtract(intervals, others),
+                    BedOperation::Complement => {
+                        let Some(record) = genome.as_ref().and_then(|g| g.get(&name)) else {
+                            return Err(std::io::Error::new(
+                                std::io::ErrorKind::InvalidInput,
+                                format!("complement requires the length of '{}' in --genome", name),
+                            ));
+                        };
+                        bed::complement(intervals, record.len as usize)
+                    }
+                    _ => intervals.to_vec(),
+                };
+                for BedPos(start, end) in result {
+                    output.write(format!("{}\t{}\t{}\n", name, start, end))?;
+                }
+            }
+        }
+        BedOperation::Slop | BedOperation::Flank => {
+            let feature_map = BedFeatureMap::from_with(&bparas.in_bed, &options)?;
+            let names = sorted_names(feature_map.iter().map(|(name, _)| name), genome.as_ref());
+            let (left, right) = (bparas.left.unwrap_or(0), bparas.right.unwrap_or(0));
+            for name in names {
+                let len = genome
+                    .as_ref()
+                    .and_then(|g| g.get(&name))
+                    .map_or(usize::MAX, |r| r.len as usize);
+                for feature in feature_map.get(&name).into_iter().flatten() {
+                    // upstream and downstream are swapped on the minus strand
+                    let (l, r) = if bparas.strand && feature.strand == Strand::Reverse {
+                        (right, left)
+                    } else {
+                        (left, right)
+                    };
+                    let BedPos(start, end) = feature.pos;
+                    let regions = if bparas.operation == BedOperation::Slop {
+                        vec![BedPos(start.saturating_sub(l), end.saturating_add(r))]
+                    } else {
+                        vec![
+                            BedPos(start.saturating_sub(l), start),
+                            BedPos(end, end.saturating_add(r)),
+                        ]
+                    };
+                    for BedPos(s, e) in regions {
+                        let (s, e) = (s.min(len), e.min(len));
+                        if s < e || bparas.operation == BedOperation::Slop {
+                            output.write(format_feature(&name, BedPos(s, e), feature))?;
+                        }
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Load the sequence lengths from a `.fai` file, or by indexing a FASTA file.
+fn load_genome(path: &str) -> Result<FaIndex, std::io::Error> {
+    if path.ends_with(".fai") {
+        FaIndex::from_fai(path)
+    } else {
+        FaIndex::build(path)
+    }
+}
+
+/// The sequence names in the order of the genome (if any), followed by the other names in lexicographic order.
+fn sorted_names<'a>(
+    names: impl Iterator<Item = &'a String>,
+    genome: Option<&FaIndex>,
+) -> Vec<String> {
+    let ranks: HashMap<&str, usize> = genome
+        .map(|g| g.records().iter().enumerate())
+        .into_iter()
+        .flatten()
+        .map(|(i, r)| (r.name.as_str(), i))
+        .collect();
+    let mut names: Vec<&String> = names.collect();
+    names.sort_unstable_by_key(|&name| (ranks.get(name.as_str()).unwrap_or(&usize::MAX), name));
+    names.into_iter().cloned().collect()
+}
+
+/// Format a feature as BED3, or as BED6 if it has any of the name, score and strand.
+fn format_feature(name: &str, pos: BedPos, feature: &BedFeature) -> String {
+    let strand = match feature.strand {
+        Strand::Forward => "+",
+        Strand::Reverse => "-",
+        Strand::Unknown => ".",
+    };
+    if feature.name.is_none() && feature.score.is_none() && feature.strand == Strand::Unknown {
+        format!("{}\t{}\t{}\n", name, pos.0, pos.1)
+    } else {
+        format!(
+            "{}\t{}\t{}\t{}\t{}\t{}\n",
+            name,
+            pos.0,
+            pos.1,
+            feature.name.as_deref().unwrap_or("."),
+            feature.score.as_deref().unwrap_or("."),
+            strand
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_feature() {
+        let mut feature = BedFeature {
+            pos: BedPos(5, 10),
+            name: None,
+            score: None,
+            strand: Strand::Unknown,
+            blocks: None,
+        };
+        assert_eq!(
+            format_feature("chr1", BedPos(0, 12), &feature),
+            "chr1\t0\t12\n"
+        );
+        feature.strand = Strand::Reverse;
+        assert_eq!(
+            format_feature("chr1", BedPos(0, 12), &feature),
+            "chr1\t0\t12\t.\t.\t-\n"
+        );
+    }
+
+    #[test]
+    fn test_complement_without_genome() {
+        let bparas = BedArgs {
+            operation: BedOperation::Complement,
+            in_bed: "tests/data/bed/a.bed".to_string(),
+            other_bed: None,
+            genome: None,
+            left: None,
+            right: None,
+            strand: false,
+            repeat_class: Vec::new(),
+        };
+        let err = bed_ops(&bparas).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+        assert!(err.to_string().contains("'chr1'"));
+    }
+}
@@ -0,0 +1,217 @@
+use flate2::read::MultiGzDecoder;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+/// Block index of a BGZF file (`.gzi`, the same format as `bgzip -i`).
+///
+/// Each entry is `(compressed_offset, uncompressed_offset)` of the start of a block.
+/// The first block `(0, 0)` is implicit and is not stored in the `.gzi` file.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct GziIndex {
+    blocks: Vec<(u64, u64)>,
+}
+impl GziIndex {
+    /// Scan the headers of a BGZF file and build the block index (without decompressing the data).
+    ///
+    /// # Errors
+    ///
+    /// Return an error if the file cannot be read or is not BGZF-compressed (e.g., plain gzip).
+    pub fn build(bgzf_path: &str) -> io::Result<Self> {
+        let reader = BufReader::new(File::open(bgzf_path)?);
+        Self::build_from_reader(reader)
+    }
+    fn build_from_reader<R: Read>(mut reader: R) -> io::Result<Self> {
+        let mut index = GziIndex::default();
+        let (mut coffset, mut uoffset): (u64, u64) = (0, 0);
+        let mut header = [0u8; 12];
+        loop {
+            // 1st block is implicit
+            if coffset > 0 {
+                index.blocks.push((coffset, uoffset));
+            }
+            match reader.read_exact(&mut header) {
+                Ok(()) => {}
+                Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => break,
+                Err(e) => return Err(e),
+            }
+            if header[0..4] != [0x1f, 0x8b, 0x08, 0x04] {
+                return Err(not_bgzf());
+            }
+            let xlen = u16::from_le_bytes([header[10], header[11]]) as usize;
+            let mut extra = vec![0u8; xlen];
+            reader.read_exact(&mut extra)?;
+            let bsize = get_bsize(&extra).ok_or_else(not_bgzf)? as u64;
+            // the rest of the block: CDATA, CRC32 and ISIZE
+            let rest = (bsize + 1)
+                .checked_sub(12 + xlen as u64)
+                .filter(|&rest| rest >= 8)
+                .ok_or_else(not_bgzf)? as usize;
+            let mut block_tail = vec![0u8; rest];
+            reader.read_exact(&mut block_tail)?;
+            let data_len = u32::from_le_bytes(block_tail[(rest - 4)..].try_into().unwrap());
+            coffset += bsize + 1;
+            uoffset += data_len as u64;
+        }
+        // drop the trailing entry which points to the end of the file
+        index.blocks.pop();
+        Ok(index)
+    }
+    /// Load an existing `.gzi` file.
+    ///
+    /// # Errors
+    ///
+    /// Return an error if the file cannot be read or the number of entries does not match the file size.
+    pub fn from_gzi(gzi_path: &str) -> io::Result<Self> {
+        let file = File::open(gzi_path)?;
+        let file_len = file.metadata()?.len();
+        Self::from_gzi_reader(BufReader::new(file), file_len)
+    }
+    fn from_gzi_reader<R: Read>(mut reader: R, file_len: u64) -> io::Result<Self> {
+        let mut buf = [0u8; 8];
+        reader.read_exact(&mut buf)?;
+        let n = u64::from_le_bytes(buf);
+        // 8 bytes of the number of entries and 16 bytes per entry
+        if n.checked_mul(16).and_then(|len| len.checked_add(8)) != Some(file_len) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("corrupt .gzi: {} entries for {} bytes", n, file_len),
+            ));
+        }
+        let mut blocks = Vec::with_capacity(n as usize);
+        for _ in 0..n {
+            reader.read_exact(&mut buf)?;
+            let coffset = u64::from_le_bytes(buf);
+            reader.read_exact(&mut buf)?;
+            let uoffset = u64::from_le_bytes(buf);
+            blocks.push((coffset, uoffset));
+        }
+        Ok(GziIndex { blocks })
+    }
+    /// Write the index in `.gzi` format.
+    pub fn write_gzi(&self, gzi_path: &str) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(gzi_path)?);
+        writer.write_all(&(self.blocks.len() as u64).to_le_bytes())?;
+        for &(coffset, uoffset) in &self.blocks {
+            writer.write_all(&coffset.to_le_bytes())?;
+            writer.write_all(&uoffset.to_le_bytes())?;
+        }
+        writer.flush()
+    }
+    /// Load `<bgzf_path>.gzi` if it exists. Otherwise build the index and write it to `<bgzf_path>.gzi`.
+    pub fn load_or_build(bgzf_path: &str) -> io::Result<Self> {
+        let gzi_path = format!("{}.gzi", bgzf_path);
+        if Path::new(&gzi_path).exists() {
+            Self::from_gzi(&gzi_path)
+        } else {
+            let index = Self::build(bgzf_path)?;
+            index.write_gzi(&gzi_path)?;
+            Ok(index)
+        }
+    }
+    /// Return `(compressed_offset, uncompressed_offset)` of the block containing the uncompressed offset `uoffset`.
+    fn locate(&self, uoffset: u64) -> (u64, u64) {
+        let idx = self.blocks.partition_point(|&(_, u)| u <= uoffset);
+        if idx == 0 {
+            (0, 0)
+        } else {
+            self.blocks[idx - 1]
+        }
+    }
+}
+
+/// Random access into a BGZF file by uncompressed offsets.
+pub struct BgzfReader {
+    file: File,
+    index: GziIndex,
+}
+impl BgzfReader {
+    pub fn new(file: File, index: GziIndex) -> Self {
+        BgzfReader { file, index }
+    }
+    /// Fill `buf` with the uncompressed bytes starting at `uoffset`.
+    /// Only the blocks covering the requested bytes are decompressed.
+    pub fn read_at(&mut self, uoffset: u64, buf: &mut [u8]) -> io::Result<()> {
+        let (block_coffset, block_uoffset) = self.index.locate(uoffset);
+        self.file.seek(SeekFrom::Start(block_coffset))?;
+        let mut decoder = MultiGzDecoder::new(&mut self.file);
+        io::copy(
+            &mut (&mut decoder).take(uoffset - block_uoffset),
+            &mut io::sink(),
+        )?;
+        decoder.read_exact(buf)
+    }
+}
+
+/// Find the `BC` subfield in the gzip extra field and return BSIZE (total block size - 1).
+fn get_bsize(extra: &[u8]) -> Option<u16> {
+    let mut i = 0;
+    while i + 4 <= extra.len() {
+        let slen = u16::from_le_bytes([extra[i + 2], extra[i + 3]]) as usize;
+        if extra[i] == b'B' && extra[i + 1] == b'C' && slen == 2 && i + 6 <= extra.len() {
+            return Some(u16::from_le_bytes([extra[i + 4], extra[i + 5]]));
+        }
+        i += 4 + slen;
+    }
+    None
+}
+fn not_bgzf() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        "the file is not BGZF-compressed (please compress it by `bgzip`)",
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_bsize() {
+        assert_eq!(get_bsize(&[b'B', b'C', 2, 0, 0x1b, 0x00]), Some(27));
+        assert_eq!(
+            get_bsize(&[b'X', b'Y', 1, 0, 9, b'B', b'C', 2, 0, 0xff, 0x01]),
+            Some(511)
+        );
+        assert_eq!(get_bsize(&[b'X', b'Y', 2, 0, 9, 9]), None);
+    }
+
+    #[test]
+    fn test_corrupt_bsize() {
+        // BSIZE smaller than the header and XLEN, or without room for CRC32 and ISIZE
+        for bsize in [5u8, 21] {
+            let mut block = vec![0x1f, 0x8b, 0x08, 0x04, 0, 0, 0, 0, 0, 0xff, 6, 0];
+            block.extend([b'B', b'C', 2, 0, bsize, 0]);
+            block.extend([0u8; 8]);
+            let err = GziIndex::build_from_reader(&block[..]).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        }
+    }
+
+    #[test]
+    fn test_from_gzi() {
+        let mut gzi = 2u64.to_le_bytes().to_vec();
+        for v in [100u64, 1000, 180, 2000] {
+            gzi.extend(v.to_le_bytes());
+        }
+        let index = GziIndex::from_gzi_reader(&gzi[..], gzi.len() as u64).unwrap();
+        assert_eq!(index.blocks, vec![(100, 1000), (180, 2000)]);
+        // a truncated file, and a huge number of entries
+        let err = GziIndex::from_gzi_reader(&gzi[..24], 24).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        gzi[..8].copy_from_slice(&(1u64 << 60).to_le_bytes());
+        let err = GziIndex::from_gzi_reader(&gzi[..], gzi.len() as u64).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_locate() {
+        let index = GziIndex {
+            blocks: vec![(100, 1000), (180, 2000)],
+        };
+        assert_eq!(index.locate(0), (0, 0));
+        assert_eq!(index.locate(999), (0, 0));
+        assert_eq!(index.locate(1000), (100, 1000));
+        assert_eq!(index.locate(2500), (180, 2000));
+    }
+}
@@ -1,5 +1,8 @@
 use clap::Parser;
-use seqtk_rs::{fqchk, nc_comp, seq, size, sub_cli, subsample, trim};
+use seqtk_rs::{
+    adapter, bed_ops, cutn, dedup, demux, faidx, fqchk, getfeat, locate, maskbed, nc_comp, primer,
+    rename, seq, size, sub_cli, subsample, subseq, trim, umi,
+};
 
 /// Here 1
 fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -29,6 +32,78 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             }
         }
 
+        sub_cli::Commands::Select(select) => {
+            if let Some(fq) = &select.in_fq {
+                subsample::select_fastx(fq, select, false)?;
+            }
+            if let Some(fa) = &select.in_fa {
+                subsample::select_fastx(fa, select, true)?;
+            }
+        }
+
+        sub_cli::Commands::Subseq(subseq) => {
+            if let Some(fq) = &subseq.in_fq {
+                subseq::subseq_fastx(fq, subseq, false)?;
+            }
+            if let Some(fa) = &subseq.in_fa {
+                subseq::subseq_fastx(fa, subseq, true)?;
+            }
+        }
+
+        sub_cli::Commands::Getfeat(getfeat) => {
+            if let Some(fq) = &getfeat.in_fq {
+                getfeat::getfeat_fastx(fq, getfeat, false)?;
+            }
+            if let Some(fa) = &getfeat.in_fa {
+                getfeat::getfeat_fastx(fa, getfeat, true)?;
+            }
+        }
+
+        sub_cli::Commands::Locate(locate) => {
+            if let Some(fq) = &locate.in_fq {
+                locate::locate_fastx(fq, locate, false)?;
+            }
+            if let Some(fa) = &locate.in_fa {
+                locate::locate_fastx(fa, locate, true)?;
+            }
+        }
+
+        sub_cli::Commands::Maskbed(maskbed) => {
+            if let Some(fq) = &maskbed.in_fq {
+                maskbed::maskbed_fastx(fq, maskbed, false)?;
+            }
+            if let Some(fa) = &maskbed.in_fa {
+                maskbed::maskbed_fastx(fa, maskbed, true)?;
+            }
+        }
+
+        sub_cli::Commands::Cutn(cutn) => {
+            if let Some(fq) = &cutn.in_fq {
+                cutn::cutn_fastx(fq, cutn, false)?;
+            }
+            if let Some(fa) = &cutn.in_fa {
+                cutn::cutn_fastx(fa, cutn, true)?;
+            }
+        }
+
+        sub_cli::Commands::Rename(rename) => {
+            if let Some(fq) = &rename.in_fq {
+                rename::rename_fastx(fq, rename, false)?;
+            }
+            if let Some(fa) = &rename.in_fa {
+                rename::rename_fastx(fa, rename, true)?;
+            }
+        }
+
+        sub_cli::Commands::Bed(bed) => {
+            sub_cli::valiation_bed_args(bed)?;
+            bed_ops::bed_ops(bed)?;
+        }
+
+        sub_cli::Commands::Faidx(faidx) => {
+            faidx::faidx(&faidx.in_fa, &faidx.regions)?;
+        }
+
         sub_cli::Commands::Size(size) => {
             if let Some(fq) = &size.in_fq {
                 size::calc_fq_size(fq)?;
@@ -39,21 +114,69 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
 
         sub_cli::Commands::Qctrim(trim) => {
-            let q_threshold = trim.q_thershold.unwrap_or(13);
-            let asciibase = trim.ascii_base.unwrap_or(33);
-            let minlen = trim.min_length.unwrap_or(30);
-            trim::trimfq(&trim.in_fq, q_threshold + asciibase, minlen)?;
+            sub_cli::valiation_qctrim_args(trim)?;
+            trim::qctrim_fastq(&trim.in_fq, trim)?;
+        }
+        sub_cli::Commands::Trimadapter(trim) => {
+            sub_cli::valiation_trimadapter_args(trim)?;
+            if let (Some(fq1), Some(fq2)) = (&trim.in_fq, &trim.in_fq2) {
+                adapter::trim_adapter_paired(fq1, fq2, trim)?;
+            } else if let Some(fq) = &trim.in_fq {
+                adapter::trim_adapter_fastx(fq, trim, false)?;
+            }
+            if let Some(fa) = &trim.in_fa {
+                adapter::trim_adapter_fastx(fa, trim, true)?;
+            }
+        }
+        sub_cli::Commands::Detectadapter(detect) => {
+            if let Some(fq) = &detect.in_fq {
+                adapter::detect_adapter_fastx(fq, detect, false)?;
+            }
+            if let Some(fa) = &detect.in_fa {
+                adapter::detect_adapter_fastx(fa, detect, true)?;
+            }
+        }
+        sub_cli::Commands::Trimprimer(trim) => {
+            if let Some(fq) = &trim.in_fq {
+                primer::trim_primer_fastx(fq, trim, false)?;
+            }
+            if let Some(fa) = &trim.in_fa {
+                primer::trim_primer_fastx(fa, trim, true)?;
+            }
+        }
+        sub_cli::Commands::Umi(umi) => {
+            umi::umi_extract(umi)?;
+        }
+        sub_cli::Commands::Demux(demux) => {
+            demux::demux_fastq(demux)?;
+        }
+        sub_cli::Commands::Dedup(dedup) => {
+            dedup::dedup_fastq(dedup)?;
         }
         sub_cli::Commands::Comp(comp) => {
             if let Some(fq) = &comp.in_fq {
                 match &comp.in_bed {
-                    Some(bed) => nc_comp::calc_fq_comp_with_bed(fq, bed, comp.exclude_masked)?,
+                    Some(bed) => nc_comp::calc_fq_comp_with_bed(
+                        fq,
+                        bed,
+                        comp.exclude_masked,
+                        comp.strict_bed,
+                        comp.name_alias.as_deref(),
+                        comp.chr_alias,
+                    )?,
                     None => nc_comp::calc_fq_comp_wo_bed(fq, comp.exclude_masked)?,
                 }
             }
             if let Some(fa) = &comp.in_fa {
                 match &comp.in_bed {
-                    Some(bed) => nc_comp::calc_fa_comp_with_bed(fa, bed, comp.exclude_masked)?,
+                    Some(bed) => nc_comp::calc_fa_comp_with_bed(
+                        fa,
+                        bed,
+                        comp.exclude_masked,
+                        comp.strict_bed,
+                        comp.name_alias.as_deref(),
+                        comp.chr_alias,
+                    )?,
                     None => nc_comp::calc_fa_comp_wo_bed(fa, comp.exclude_masked)?,
                 }
             }
@@ -0,0 +1,153 @@
+use crate::bed::{self, BedPos};
+use crate::io_utils::{FaReader, FqReader, FxWriter};
+use crate::maskbed::{find_runs, get_selected_indexes};
+use crate::record::RecordType;
+use crate::sub_cli::{CutnArgs, MaskClass};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+/// Parses FASTA/Q file and splits the sequences into contigs at the N gaps of at least `--min-gap` bases.
+/// Outputs the contigs to [`std::io::stdout()`], named `name:start-end` (1-based, inclusive) by their positions.
+///
+/// * `--gap-bed` writes the removed gaps as BED3 (0-based, half-open).
+/// * `--agp` writes AGP 2.1 to rebuild the scaffolds from the contigs. The gaps at both ends are left out of the objects.
+///
+/// # Arguments
+///
+/// Check the arguments by `--help`
+///
+/// # Errors
+///
+/// Return an error if the operation cannot be completed.
+pub fn cutn_fastx(fx_path: &str, cparas: &CutnArgs, is_fasta: bool) -> Result<(), std::io::Error> {
+    let min_gap = cparas.min_gap.unwrap_or(1000) as usize;
+    let mut writer = FxWriter::new(is_fasta);
+    let mut gap_writer = match &cparas.gap_bed {
+        Some(path) => Some(BufWriter::new(File::create(path)?)),
+        None => None,
+    };
+    let mut agp_writer = match &cparas.agp {
+        Some(path) => {
+            let mut w = BufWriter::new(File::create(path)?);
+            writeln!(w, "##agp-version\t2.1")?;
+            Some(w)
+        }
+        None => None,
+    };
+    let mut cut_record = |read: &dyn RecordType| -> Result<(), std::io::Error> {
+        let (contigs, gaps) = split_at_gaps(read.seq(), min_gap);
+        for &BedPos(start, end) in &contigs {
+            let id = format!("{}:{}-{}", read.id(), start + 1, end);
+            let qual = if is_fasta {
+                &[][..]
+            } else {
+                &read.qual()[start..end]
+            };
+            writer.write(&id, &read.seq()[start..end], read.desc(), qual)?;
+        }
+        if let Some(w) = gap_writer.as_mut() {
+            for BedPos(start, end) in &gaps {
+                writeln!(w, "{}\t{}\t{}", read.id(), start, end)?;
+            }
+        }
+        if let Some(w) = agp_writer.as_mut() {
+            for line in agp_lines(read.id(), &contigs) {
+                writeln!(w, "{}", line)?;
+            }
+        }
+        Ok(())
+    };
+    if is_fasta {
+        let fa_iter = FaReader::new(fx_path)?;
+        for record in fa_iter.records() {
+            match record {
+                Ok(read) => cut_record(&read)?,
+                Err(e) => eprintln!("Error read FASTA: {}", e),
+            }
+        }
+    } else {
+        let fq_iter = FqReader::new(fx_path)?;
+        for record in fq_iter.records() {
+            match record {
+                Ok(read) => cut_record(&read)?,
+                Err(e) => eprintln!("Error read FASTQ: {}", e),
+            }
+        }
+    }
+    if let Some(mut w) = gap_writer {
+        w.flush()?;
+    }
+    if let Some(mut w) = agp_writer {
+        w.flush()?;
+    }
+    Ok(())
+}
+
+/// Return the contigs and the N gaps (of at least `min_gap` bases) of a sequence.
+fn split_at_gaps(seq: &[u8], min_gap: usize) -> (Vec<BedPos>, Vec<BedPos>) {
+    let gaps = find_runs(seq, &get_selected_indexes(&[MaskClass::N]), min_gap);
+    let contigs = bed::complement(&gaps, seq.len());
+    (contigs, gaps)
+}
+
+/// AGP lines of an object (scaffold) made of the contigs and the gaps between them.
+/// The object coordinates start at the first base of the first contig.
+fn agp_lines(object: &str, contigs: &[BedPos]) -> Vec<String> {
+    let Some(&BedPos(offset, _)) = contigs.first() else {
+        return Vec::new();
+    };
+    let mut lines = Vec::new();
+    let mut prev_end: Option<usize> = None;
+    for &BedPos(start, end) in contigs {
+        if let Some(gap_start) = prev_end {
+            lines.push(format!(
+                "{}\t{}\t{}\t{}\tN\t{}\tscaffold\tyes\tunspecified",
+                object,
+                gap_start - offset + 1,
+                start - offset,
+                lines.len() + 1,
+                start - gap_start
+            ));
+        }
+        lines.push(format!(
+            "{}\t{}\t{}\t{}\tW\t{}:{}-{}\t1\t{}\t+",
+            object,
+            start - offset + 1,
+            end - offset,
+            lines.len() + 1,
+            object,
+            start + 1,
+            end,
+            end - start
+        ));
+        prev_end = Some(end);
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_at_gaps() {
+        let seq = b"NNNACGTNNNNacNtNNNNN";
+        assert_eq!(
+            split_at_gaps(seq, 3),
+            (
+                vec![BedPos(3, 7), BedPos(11, 15)],
+                vec![BedPos(0, 3), BedPos(7, 11), BedPos(15, 20)]
+            )
+        );
+        assert_eq!(
+            agp_lines("s1", &split_at_gaps(seq, 3).0),
+            vec![
+                "s1\t1\t4\t1\tW\ts1:4-7\t1\t4\t+",
+                "s1\t5\t8\t2\tN\t4\tscaffold\tyes\tunspecified",
+                "s1\t9\t12\t3\tW\ts1:12-15\t1\t4\t+",
+            ]
+        );
+        assert_eq!(split_at_gaps(b"NNNN", 1), (vec![], vec![BedPos(0, 4)]));
+        assert!(agp_lines("s2", &[]).is_empty());
+    }
+}
@@ -0,0 +1,223 @@
+use crate::io_utils::{FqReader, FxWriter};
+use crate::sub_cli::DedupArgs;
+use bio::io::fastq;
+use std::collections::{BTreeMap, HashMap};
+
+/// The cap of the consensus quality values
+const MAX_QUAL: u8 = 41;
+/// The quality value of the positions without votes or disagreeing reads
+const MIN_QUAL: u8 = 2;
+
+/// Parses a FASTQ file with UMIs in the read names (see `umi`) and collapses the reads of each UMI family into a consensus read.
+/// Outputs the consensus reads to [`std::io::stdout()`] and the family size distribution to [`std::io::stderr()`].
+///
+/// Reads are grouped by their start sequence (the first `--start-len` bases), and their UMIs are clustered
+/// by the directional adjacency of UMI-tools: UMI `b` joins the family of UMI `a` if they differ by at most
+/// `--distance` bases and `count(a) >= 2 * count(b) - 1`.
+///
+/// # Arguments
+///
+/// Check the arguments by `--help`
+///
+/// # Errors
+///
+/// Return an error if a read name has no UMI, or the operation cannot be completed.
+pub fn dedup_fastq(dparas: &DedupArgs) -> Result<(), std::io::Error> {
+    let separator = dparas.separator.as_deref().unwrap_or("_");
+    let max_distance = dparas.distance.unwrap_or(1);
+    let start_len = dparas.start_len.unwrap_or(10);
+    let min_size = dparas.min_size.unwrap_or(1);
+    let mut reads = Vec::new();
+    // start sequence -> UMI -> read indexes
+    let mut groups: HashMap<Vec<u8>, HashMap<Vec<u8>, Vec<usize>>> = HashMap::new();
+    for record in FqReader::new(&dparas.in_fq)?.records() {
+        match record {
+            Ok(read) => {
+                let Some((_, umi)) = read.id().rsplit_once(separator) else {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        format!("no UMI after '{}' in the name of {}", separator, read.id()),
+                    ));
+                };
+                let start = read.seq()[..start_len.min(read.seq().len())].to_ascii_uppercase();
+                groups
+                    .entry(start)
+                    .or_default()
+                    .entry(umi.as_bytes().to_ascii_uppercase())
+                    .or_default()
+                    .push(reads.len());
+                reads.push(read);
+            }
+            Err(e) => eprintln!("Error read FASTQ: {}", e),
+        }
+    }
+    let mut families: Vec<Vec<usize>> = groups
+        .into_values()
+        .flat_map(|umis| cluster_directional(umis.into_iter().collect(), max_distance))
+        .collect();
+    // in the order of the first reads
+    families.sort_unstable_by_key(|family| family[0]);
+    let mut sizes: BTreeMap<usize, usize> = BTreeMap::new();
+    let mut writer = FxWriter::new(false);
+    let mut output = 0;
+    for family in &families {
+        *sizes.entry(family.len()).or_default() += 1;
+        if family.len() < min_size {
+            continue;
+        }
+        let members: Vec<&fastq::Record> = family.iter().map(|&i| &reads[i]).collect();
+        let (seq, qual) = consensus(&members);
+        writer.write(
+            members[0].id(),
+            &seq,
+            Some(&format!("family_size={}", family.len())),
+            &qual,
+        )?;
+        output += 1;
+    }
+    eprintln!(
+        "Reads: {}, families: {}, output (family size >= {}): {}",
+        reads.len(),
+        families.len(),
+        min_size,
+        output
+    );
+    eprintln!("#family_size\tfamilies\treads");
+    for (size, count) in sizes {
+        eprintln!("{}\t{}\t{}", size, count, size * count);
+    }
+    Ok(())
+}
+
+/// Cluster the UMIs (with their read indexes) by the directional adjacency, and return the read indexes of each family.
+/// Every family grows from the most abundant UMI left (the smaller UMI on a tie).
+fn cluster_directional(
+    mut umis: Vec<(Vec<u8>, Vec<usize>)>,
+    max_distance: usize,
+) -> Vec<Vec<usize>> {
+    umis.sort_unstable_by(|a, b| b.1.len().cmp(&a.1.len()).then_with(|| a.0.cmp(&b.0)));
+    let mut assigned = vec![false; umis.len()];
+    let mut families = Vec::new();
+    for root in 0..umis.len() {
+        if assigned[root] {
+            continue;
+        }
+        assigned[root] = true;
+        let mut queue = vec![root];
+        let mut family = Vec::new();
+        while let Some(node) = queue.pop() {
+            family.extend(&umis[node].1);
+            let count = umis[node].1.len();
+            for other in 0..umis.len() {
+                if !assigned[other]
+                    && count + 1 >= 2 * umis[other].1.len()
+                    && is_within(&umis[node].0, &umis[other].0, max_distance)
+                {
+                    assigned[other] = true;
+                    queue.push(other);
+                }
+            }
+        }
+        family.sort_unstable();
+        families.push(family);
+    }
+    families
+}
+
+/// Whether the UMIs have the same length and differ by at most `max_distance` bases.
+fn is_within(a: &[u8], b: &[u8], max_distance: usize) -> bool {
+    a.len() == b.len() && a.iter().zip(b).filter(|(x, y)| x != y).count() <= max_distance
+}
+
+/// The per-position majority consensus of the reads, as long as the longest length covered by at least half of them.
+/// The quality of a base is the sum of the agreeing qualities minus that of the disagreeing ones (Phred+33, capped at 2..=41).
+fn consensus(reads: &[&fastq::Record]) -> (Vec<u8>, Vec<u8>) {
+    let mut lens: Vec<usize> = reads.iter().map(|r| r.seq().len()).collect();
+    lens.sort_unstable_by(|a, b| b.cmp(a));
+    let len = lens[(lens.len() - 1) / 2];
+    let mut seq = Vec::with_capacity(len);
+    let mut qual = Vec::with_capacity(len);
+    for i in 0..len {
+        // (votes, sum of qualities) of A, C, G and T
+        let mut votes = [(0usize, 0usize); 4];
+        for read in reads.iter().filter(|r| i < r.seq().len()) {
+            if let Some(b) = b"ACGT"
+                .iter()
+                .position(|&b| b == read.seq()[i].to_ascii_uppercase())
+            {
+                votes[b].0 += 1;
+                votes[b].1 += read.qual()[i].saturating_sub(33) as usize;
+            }
+        }
+        let mut best = 0;
+        for b in 1..4 {
+            if votes[b] > votes[best] {
+                best = b;
+            }
+        }
+        if votes[best].0 == 0 {
+            seq.push(b'N');
+            qual.push(MIN_QUAL + 33);
+            continue;
+        }
+        let disagreeing: usize = votes.iter().map(|v| v.1).sum::<usize>() - votes[best].1;
+        let q = votes[best]
+            .1
+            .saturating_sub(disagreeing)
+            .clamp(MIN_QUAL as usize, MAX_QUAL as usize);
+        seq.push(b"ACGT"[best]);
+        qual.push(q as u8 + 33);
+    }
+    (seq, qual)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cluster_directional() {
+        let umis = vec![
+            (b"AAAA".to_vec(), vec![0, 1, 2, 3]),
+            (b"AAAT".to_vec(), vec![4]),
+            (b"AATT".to_vec(), vec![5, 6]),
+            (b"AATG".to_vec(), vec![7]),
+            (b"CCCC".to_vec(), vec![8]),
+        ];
+        // AATT is not absorbed by AAAT (1 < 2 * 2 - 1)
+        assert_eq!(
+            cluster_directional(umis.clone(), 1),
+            vec![vec![0, 1, 2, 3, 4], vec![5, 6, 7], vec![8]]
+        );
+        assert_eq!(
+            cluster_directional(umis.clone(), 0),
+            vec![vec![0, 1, 2, 3], vec![5, 6], vec![4], vec![7], vec![8]]
+        );
+        // AAAA absorbs AATT and AATG with 2 mismatches
+        assert_eq!(
+            cluster_directional(umis, 2),
+            vec![vec![0, 1, 2, 3, 4, 5, 6, 7], vec![8]]
+        );
+        assert!(!is_within(b"AAAA", b"AAA", 4));
+    }
+
+    #[test]
+    fn test_consensus() {
+        let r1 = fastq::Record::with_attrs("r1", None, b"ACGTAC", b"IIIII#");
+        let r2 = fastq::Record::with_attrs("r2", None, b"ACGAA", b"IIII5");
+        let r3 = fastq::Record::with_attrs("r3", None, b"ACcTNCGG", b"I5IIIIII");
+        let (seq, qual) = consensus(&[&r1, &r2, &r3]);
+        // the length 6 is covered by 2 of the 3 reads
+        assert_eq!(seq, b"ACGTAC".to_vec());
+        // A: 40 * 3 capped, G: 40 + 40 - 40, T: 40 + 40 - 40, A: 40 + 20 (N does not vote), C: 2 + 40
+        assert_eq!(qual, b"JJIIJJ".to_vec());
+        let (seq, qual) = consensus(&[&r1]);
+        assert_eq!((seq, qual), (b"ACGTAC".to_vec(), b"IIIII#".to_vec()));
+        // a tie is broken by the qualities
+        let r4 = fastq::Record::with_attrs("r4", None, b"T", b"5");
+        let r5 = fastq::Record::with_attrs("r5", None, b"G", b"I");
+        assert_eq!(consensus(&[&r4, &r5]), (b"G".to_vec(), b"5".to_vec()));
+        let r6 = fastq::Record::with_attrs("r6", None, b"N", b"I");
+        assert_eq!(consensus(&[&r6]), (b"N".to_vec(), b"#".to_vec()));
+    }
+}
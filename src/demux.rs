@@ -0,0 +1,368 @@
+use crate::io_utils::{buffer_reader_maybe_gz, FqReader, FxWriter};
+use crate::sub_cli::{BarcodeLocation, DemuxArgs};
+use bio::io::fastq;
+use std::collections::HashSet;
+use std::io::BufRead;
+use std::path::Path;
+
+const UNDETERMINED: &str = "undetermined";
+const AMBIGUOUS: &str = "ambiguous";
+
+/// Parses FASTQ files (read 1, and optionally read 2) and assigns the reads (pairs) to the samples of a sample sheet by their barcodes.
+/// Outputs the reads to `<out_dir>/<sample>.fastq` (`<sample>_R1.fastq` and `<sample>_R2.fastq` for pairs),
+/// together with `undetermined` and `ambiguous`, and the numbers of reads per sample to [`std::io::stderr()`].
+///
+/// A read is assigned to the sample with the fewest mismatches if every barcode has at most `--max-mismatches`.
+/// It is ambiguous if several samples have the fewest mismatches, and undetermined if no sample matches.
+///
+/// # Arguments
+///
+/// Check the arguments by `--help`
+///
+/// # Errors
+///
+/// Return an error if the sample sheet is invalid, the files have different numbers of reads, or the operation cannot be completed.
+pub fn demux_fastq(dparas: &DemuxArgs) -> Result<(), std::io::Error> {
+    let samples = parse_sample_sheet(buffer_reader_maybe_gz(&dparas.sample_sheet)?)?;
+    let location = dparas.location.unwrap_or(BarcodeLocation::Header);
+    if location == BarcodeLocation::Inline
+        && samples[0].barcodes.len() > 1
+        && dparas.in_fq2.is_none()
+    {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "inline dual barcodes require read 2 (--in-fq2)",
+        ));
+    }
+    let max_mismatches = dparas.max_mismatches.unwrap_or(1);
+    std::fs::create_dir_all(&dparas.out_dir)?;
+    // the samples, then the undetermined and the ambiguous reads
+    let mut writers = Vec::with_capacity(samples.len() + 2);
+    for name in samples
+        .iter()
+        .map(|s| s.name.as_str())
+        .chain([UNDETERMINED, AMBIGUOUS])
+    {
+        writers.push(SampleWriter::new(
+            &dparas.out_dir,
+            name,
+            dparas.in_fq2.is_some(),
+        )?);
+    }
+    let (undetermined, ambiguous) = (samples.len(), samples.len() + 1);
+    // (reads, reads with mismatches) of the samples
+    let mut counts = vec![(0, 0); samples.len() + 2];
+    let mut fq2_iter = match &dparas.in_fq2 {
+        Some(path) => Some((path, FqReader::new(path)?.records())),
+        None => None,
+    };
+    let unpaired = |fq2_path: &str| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!(
+                "{} and {} have different numbers of reads",
+                dparas.in_fq, fq2_path
+            ),
+        )
+    };
+    for record1 in FqReader::new(&dparas.in_fq)?.records() {
+        let record2 = match fq2_iter.as_mut() {
+            Some((path, iter)) => Some(iter.next().ok_or_else(|| unpaired(path))?),
+            None => None,
+        };
+        let (read1, read2) = match (record1, record2.transpose()) {
+            (Ok(read1), Ok(read2)) => (read1, read2),
+            (Err(e), _) | (_, Err(e)) => {
+                eprintln!("Error read FASTQ: {}", e);
+                continue;
+            }
+        };
+        let barcodes: Vec<&[u8]> = match location {
+            BarcodeLocation::Header => header_barcodes(&read1),
+            BarcodeLocation::Inline => {
+                let mut barcodes = vec![read1.seq()];
+                if let Some(read2) = &read2 {
+                    barcodes.push(read2.seq());
+                }
+                barcodes
+            }
+        };
+        let (idx, starts) = match assign(&samples, &barcodes, max_mismatches) {
+            Assignment::Sample(idx, mismatches) => {
+                counts[idx].1 += usize::from(mismatches > 0);
+                let starts = match location {
+                    BarcodeLocation::Header => (0, 0),
+                    BarcodeLocation::Inline => {
+                        let lens = &samples[idx].barcodes;
+                        (lens[0].len(), lens.get(1).map_or(0, |b| b.len()))
+                    }
+                };
+                (idx, starts)
+            }
+            Assignment::Undetermined => (undetermined, (0, 0)),
+            Assignment::Ambiguous => (ambiguous, (0, 0)),
+        };
+        counts[idx].0 += 1;
+        let writer = &mut writers[idx];
+        write_trimmed(&mut writer.0, &read1, starts.0)?;
+        if let Some(read2) = &read2 {
+            write_trimmed(writer.1.as_mut().unwrap(), read2, starts.1)?;
+        }
+    }
+    if let Some((path, mut iter)) = fq2_iter {
+        if iter.next().is_some() {
+            return Err(unpaired(path));
+        }
+    }
+    let total: usize = counts.iter().map(|c| c.0).sum();
+    eprintln!(
+        "Reads: {}, assigned: {}, undetermined: {}, ambiguous: {}",
+        total,
+        total - counts[undetermined].0 - counts[ambiguous].0,
+        counts[undetermined].0,
+        counts[ambiguous].0
+    );
+    eprintln!("#sample\tbarcode\treads\tmismatched");
+    for (sample, (reads, mismatched)) in samples.iter().zip(&counts) {
+        let barcode: Vec<String> = sample
+            .barcodes
+            .iter()
+            .map(|b| String::from_utf8_lossy(b).to_string())
+            .collect();
+        eprintln!(
+            "{}\t{}\t{}\t{}",
+            sample.name,
+            barcode.join("+"),
+            reads,
+            mismatched
+        );
+    }
+    Ok(())
+}
+
+/// The output of read 1 and read 2 (for pairs) of a sample.
+struct SampleWriter(FxWriter, Option<FxWriter>);
+impl SampleWriter {
+    fn new(out_dir: &str, name: &str, paired: bool) -> Result<Self, std::io::Error> {
+        let path = |suffix: &str| {
+            Path::new(out_dir)
+                .join(format!("{}{}.fastq", name, suffix))
+                .to_string_lossy()
+                .to_string()
+        };
+        if paired {
+            Ok(SampleWriter(
+                FxWriter::from_path(&path("_R1"), false)?,
+                Some(FxWriter::from_path(&path("_R2"), false)?),
+            ))
+        } else {
+            Ok(SampleWriter(FxWriter::from_path(&path(""), false)?, None))
+        }
+    }
+}
+
+fn write_trimmed(
+    writer: &mut FxWriter,
+    read: &fastq::Record,
+    start: usize,
+) -> Result<(), std::io::Error> {
+    let start = start.min(read.seq().len());
+    writer.write(
+        read.id(),
+        &read.seq()[start..],
+        read.desc(),
+        &read.qual()[start..],
+    )
+}
+
+/// The barcodes of the last field of an Illumina header (e.g., `1:N:0:ACGTACGT+TTGGCCAA`).
+fn header_barcodes(read: &fastq::Record) -> Vec<&[u8]> {
+    match read.desc().and_then(|desc| desc.rsplit(':').next()) {
+        Some(field) => field.split('+').map(|b| b.as_bytes()).collect(),
+        None => Vec::new(),
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct Sample {
+    name: String,
+    /// The first (e.g., i7) and the second (e.g., i5) barcodes
+    barcodes: Vec<Vec<u8>>,
+}
+
+/// Read `sample barcode1 [barcode2]` lines (separated by whitespace, or `barcode1+barcode2`).
+/// Empty lines and lines starting with `#` are ignored.
+fn parse_sample_sheet(reader: impl BufRead) -> Result<Vec<Sample>, std::io::Error> {
+    let invalid = |msg: String| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("invalid sample sheet: {}", msg),
+        )
+    };
+    let mut samples: Vec<Sample> = Vec::new();
+    let mut names = HashSet::new();
+    let mut combinations = HashSet::new();
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut fields = line.split_whitespace();
+        let name = fields.next().unwrap_or_default().to_string();
+        // the name is a part of the output file names
+        if name.contains("..")
+            || !name
+                .bytes()
+                .all(|c| c.is_ascii_alphanumeric() || b"._-".contains(&c))
+        {
+            return Err(invalid(format!(
+                "the sample name {} must consist of A-Z, a-z, 0-9, '.', '_' and '-' (without '..')",
+                name
+            )));
+        }
+        let barcodes: Vec<Vec<u8>> = fields
+            .flat_map(|f| f.split('+'))
+            .map(|b| b.as_bytes().to_ascii_uppercase())
+            .collect();
+        if barcodes.is_empty() || barcodes.len() > 2 {
+            return Err(invalid(format!("{} must have one or two barcodes", name)));
+        }
+        if barcodes
+            .iter()
+            .any(|b| b.is_empty() || b.iter().any(|c| !b"ACGT".contains(c)))
+        {
+            return Err(invalid(format!(
+                "the barcodes of {} must be A, C, G and T",
+                name
+            )));
+        }
+        if let Some(first) = samples.first() {
+            if first.barcodes.len() != barcodes.len()
+                || first
+                    .barcodes
+                    .iter()
+                    .zip(&barcodes)
+                    .any(|(a, b)| a.len() != b.len())
+            {
+                return Err(invalid(format!(
+                    "the barcodes of {} differ from those of {} in number or length",
+                    name, first.name
+                )));
+            }
+        }
+        if name == UNDETERMINED || name == AMBIGUOUS || !names.insert(name.clone()) {
+            return Err(invalid(format!("{} is reserved or duplicated", name)));
+        }
+        if !combinations.insert(barcodes.clone()) {
+            return Err(invalid(format!("the barcodes of {} are duplicated", name)));
+        }
+        samples.push(Sample { name, barcodes });
+    }
+    if samples.is_empty() {
+        return Err(invalid("no samples".to_string()));
+    }
+    Ok(samples)
+}
+
+#[derive(Debug, PartialEq)]
+enum Assignment {
+    /// Index and total mismatches of the sample
+    Sample(usize, usize),
+    Undetermined,
+    Ambiguous,
+}
+
+/// Assign the barcodes of a read (prefixes are compared) to the sample with the fewest mismatches,
+/// if every barcode has at most `max_mismatches`.
+fn assign(samples: &[Sample], barcodes: &[&[u8]], max_mismatches: usize) -> Assignment {
+    let mut best = Assignment::Undetermined;
+    let mut best_mismatches = usize::MAX;
+    'sample: for (idx, sample) in samples.iter().enumerate() {
+        if barcodes.len() < sample.barcodes.len() {
+            return Assignment::Undetermined;
+        }
+        let mut total = 0;
+        for (expected, observed) in sample.barcodes.iter().zip(barcodes) {
+            let mismatches = hamming(expected, observed);
+            if mismatches > max_mismatches {
+                continue 'sample;
+            }
+            total += mismatches;
+        }
+        if total < best_mismatches {
+            best = Assignment::Sample(idx, total);
+            best_mismatches = total;
+        } else if total == best_mismatches {
+            best = Assignment::Ambiguous;
+        }
+    }
+    best
+}
+
+/// Mismatches of `expected` and the prefix of `observed` (the missing bases are mismatches).
+fn hamming(expected: &[u8], observed: &[u8]) -> usize {
+    let compared = expected.len().min(observed.len());
+    expected
+        .iter()
+        .zip(observed)
+        .filter(|(e, o)| **e != o.to_ascii_uppercase())
+        .count()
+        + expected.len()
+        - compared
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sample_sheet() {
+        let sheet = b"# sample\ti7\ti5\nS1\tACGT\tTTGG\n\nS2 ggcc+aacc\n";
+        let samples = parse_sample_sheet(&sheet[..]).unwrap();
+        assert_eq!(
+            samples,
+            vec![
+                Sample {
+                    name: "S1".to_string(),
+                    barcodes: vec![b"ACGT".to_vec(), b"TTGG".to_vec()],
+                },
+                Sample {
+                    name: "S2".to_string(),
+                    barcodes: vec![b"GGCC".to_vec(), b"AACC".to_vec()],
+                },
+            ]
+        );
+        // invalid bases, lengths, numbers of barcodes, names and duplicates
+        assert!(parse_sample_sheet(&b"S1\tACGN\n"[..]).is_err());
+        assert!(parse_sample_sheet(&b"S1\tACGT\nS2\tACG\n"[..]).is_err());
+        assert!(parse_sample_sheet(&b"S1\tACGT\nS2\tACGA\tTTGG\n"[..]).is_err());
+        assert!(parse_sample_sheet(&b"S1\n"[..]).is_err());
+        assert!(parse_sample_sheet(&b"S1\tACGT\nS1\tACGA\n"[..]).is_err());
+        assert!(parse_sample_sheet(&b"undetermined\tACGT\n"[..]).is_err());
+        assert!(parse_sample_sheet(&b"S1\tACGT\nS2\tACGT\n"[..]).is_err());
+        assert!(parse_sample_sheet(&b"# empty\n"[..]).is_err());
+        // names unsafe as file names
+        assert!(parse_sample_sheet(&b"../S1\tACGT\n"[..]).is_err());
+        assert!(parse_sample_sheet(&b"S1\\a\tACGT\n"[..]).is_err());
+        assert!(parse_sample_sheet(&b"S..1\tACGT\n"[..]).is_err());
+        assert!(parse_sample_sheet(&b"S-1_a.2\tACGT\n"[..]).is_ok());
+    }
+
+    #[test]
+    fn test_assign() {
+        let samples = parse_sample_sheet(&b"S1\tACGTAC\nS2\tGGCCTT\nS3\tACGTAA\n"[..]).unwrap();
+        assert_eq!(assign(&samples, &[b"GGCCTT"], 1), Assignment::Sample(1, 0));
+        assert_eq!(
+            assign(&samples, &[b"GGCATTACGT"], 1),
+            Assignment::Sample(1, 1)
+        );
+        // S1 wins over S3 with fewer mismatches
+        assert_eq!(assign(&samples, &[b"ACGTAC"], 1), Assignment::Sample(0, 0));
+        assert_eq!(assign(&samples, &[b"ACGTAG"], 1), Assignment::Ambiguous);
+        assert_eq!(assign(&samples, &[b"ACGTAG"], 0), Assignment::Undetermined);
+        assert_eq!(assign(&samples, &[b"GGCNTT"], 0), Assignment::Undetermined);
+        // short barcodes and missing barcodes
+        assert_eq!(assign(&samples, &[b"GGCCT"], 1), Assignment::Sample(1, 1));
+        assert_eq!(assign(&samples, &[], 1), Assignment::Undetermined);
+    }
+}
@@ -39,6 +39,28 @@ pub fn revcomp(seq: &mut [u8]) {
     seq.reverse();
 }
 
+/// IUPAC nucleotide code to a bitmask of A: 1, C: 2, G: 4, T(U): 8 (case-insensitive). Others: 0
+pub fn get_iupac_mask(s: u8) -> u8 {
+    match s.to_ascii_uppercase() {
+        b'A' => 0b0001,
+        b'C' => 0b0010,
+        b'G' => 0b0100,
+        b'T' | b'U' => 0b1000,
+        b'R' => 0b0101, // A/G
+        b'Y' => 0b1010, // C/T
+        b'S' => 0b0110, // C/G
+        b'W' => 0b1001, // A/T
+        b'K' => 0b1100, // G/T
+        b'M' => 0b0011, // A/C
+        b'B' => 0b1110, // C/G/T
+        b'D' => 0b1101, // A/G/T
+        b'H' => 0b1011, // A/C/T
+        b'V' => 0b0111, // A/C/G
+        b'N' => 0b1111,
+        _ => 0,
+    }
+}
+
 pub struct SeqComp;
 impl SeqComp {
     /// ASCII to IUPAC nc comsidering masked bases
@@ -59,7 +81,7 @@ impl SeqComp {
         14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14,
         14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14,
     ];
-    fn get_iupac_index_from_u8(s: u8) -> usize {
+    pub fn get_iupac_index_from_u8(s: u8) -> usize {
         Self::ASCII_TO_IUPAC_NC_IDX[s as usize]
     }
     /// Input IUPAC index and output 1: CG, 2: CG(reverse complemet), 3: others
@@ -148,6 +170,24 @@ mod tests {
     use super::*;
     use bio::io::fastq::Record;
 
+    #[test]
+    fn test_iupac_mask() {
+        assert_eq!(get_iupac_mask(b'a'), get_iupac_mask(b'A'));
+        assert_eq!(
+            get_iupac_mask(b'R'),
+            get_iupac_mask(b'A') | get_iupac_mask(b'G')
+        );
+        assert_eq!(get_iupac_mask(b'n'), 0b1111);
+        assert_eq!(get_iupac_mask(b'-'), 0);
+        // the mask of the complement is the complement of the mask
+        for &b in b"ACGTRYSWKMBDHVN" {
+            let m = get_iupac_mask(b);
+            let rc_m = get_iupac_mask(complement(&b));
+            let swapped = (m & 1) << 3 | (m & 2) << 1 | (m & 4) >> 1 | (m & 8) >> 3;
+            assert_eq!(rc_m, swapped, "{}", b as char);
+        }
+    }
+
     #[test]
     fn test_get_cp_index() {
         fn get_cp(cur_b: u8, next_b: u8, excluded_masked: bool) -> usize {
@@ -0,0 +1,419 @@
+use crate::bgzf::{BgzfReader, GziIndex};
+use crate::io_utils::{buffer_reader_maybe_gz, FxWriter};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+/// One line of a samtools-compatible `.fai` index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FaiRecord {
+    pub name: String,
+    /// Number of bases of the sequence
+    pub len: u64,
+    /// Byte offset of the first base of the sequence
+    pub offset: u64,
+    /// Number of bases on each line
+    pub line_bases: u64,
+    /// Number of bytes on each line (including the newline)
+    pub line_width: u64,
+}
+impl FaiRecord {
+    /// Byte offset of the 0-based position `pos`.
+    fn pos_to_offset(&self, pos: u64) -> u64 {
+        self.offset + (pos / self.line_bases) * self.line_width + pos % self.line_bases
+    }
+}
+
+/// Samtools-compatible FASTA index (`.fai`).
+#[derive(Debug, Default)]
+pub struct FaIndex {
+    records: Vec<FaiRecord>,
+    name_to_idx: HashMap<String, usize>,
+}
+impl FaIndex {
+    /// Scan a FASTA file and build the index.
+    /// For a `.gz` file, the offsets are positions in the decompressed data.
+    ///
+    /// # Errors
+    ///
+    /// Return an error if the file cannot be read, the line lengths of a sequence are not uniform,
+    /// or a blank line is followed by more lines of the sequence.
+    pub fn build(fa_path: &str) -> io::Result<Self> {
+        let reader = buffer_reader_maybe_gz(fa_path)?;
+        Self::build_from_reader(reader)
+    }
+    fn build_from_reader<R: BufRead>(mut reader: R) -> io::Result<Self> {
+        let mut index = FaIndex::default();
+        let mut cur: Option<FaiRecord> = None;
+        // (bases, bytes) of the previous line, which must be a full line if another line follows
+        let mut last_line: Option<(u64, u64)> = None;
+        let mut has_blank = false;
+        let mut offset: u64 = 0;
+        let mut line: Vec<u8> = Vec::new();
+        loop {
+            line.clear();
+            let n = reader.read_until(b'\n', &mut line)? as u64;
+            if n == 0 {
+                break;
+            }
+            if line[0] == b'>' {
+                if let Some(rec) = cur.take() {
+                    index.push(rec)?;
+                }
+                let header = String::from_utf8_lossy(&line[1..]);
+                let name = header.split_whitespace().next().unwrap_or("").to_string();
+                cur = Some(FaiRecord {
+                    name,
+                    len: 0,
+                    offset: offset + n,
+                    line_bases: 0,
+                    line_width: 0,
+                });
+                last_line = None;
+                has_blank = false;
+            } else if let Some(rec) = cur.as_mut() {
+                let bases = line
+                    .iter()
+                    .take_while(|&&c| c != b'\n' && c != b'\r')
+                    .count() as u64;
+                if bases == 0 {
+                    // allowed only at the end of a sequence
+                    has_blank = true;
+                } else {
+                    let different = |msg: &str| {
+                        io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            format!("{} in sequence '{}'", msg, rec.name),
+                        )
+                    };
+                    if has_blank {
+                        return Err(different("blank line"));
+                    }
+                    if let Some((prev_bases, prev_width)) = last_line {
+                        if prev_bases != rec.line_bases || prev_width != rec.line_width {
+                            return Err(different("different line length"));
+                        }
+                    }
+                    if rec.line_bases == 0 {
+                        rec.line_bases = bases;
+                        rec.line_width = n;
+                    } else if bases > rec.line_bases {
+                        return Err(different("different line length"));
+                    }
+                    last_line = Some((bases, n));
+                    rec.len += bases;
+                }
+            }
+            offset += n;
+        }
+        if let Some(rec) = cur.take() {
+            index.push(rec)?;
+        }
+        Ok(index)
+    }
+    fn push(&mut self, rec: FaiRecord) -> io::Result<()> {
+        if self.name_to_idx.contains_key(&rec.name) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("duplicate sequence name '{}'", rec.name),
+            ));
+        }
+        self.name_to_idx
+            .insert(rec.name.clone(), self.records.len());
+        self.records.push(rec);
+        Ok(())
+    }
+    /// Load an existing `.fai` file.
+    ///
+    /// # Errors
+    ///
+    /// Return an error if the file cannot be read or a line is malformed
+    /// (e.g., no bases per line for a non-empty sequence, or fewer bytes than bases per line).
+    pub fn from_fai(fai_path: &str) -> io::Result<Self> {
+        Self::from_fai_reader(BufReader::new(File::open(fai_path)?))
+    }
+    fn from_fai_reader<R: BufRead>(reader: R) -> io::Result<Self> {
+        let mut index = FaIndex::default();
+        for line in reader.lines() {
+            let line = line?;
+            if line.is_empty() {
+                continue;
+            }
+            let cols: Vec<&str> = line.split('\t').collect();
+            let parse = |i: usize| -> io::Result<u64> {
+                cols.get(i)
+                    .and_then(|v| v.parse::<u64>().ok())
+                    .ok_or_else(|| {
+                        io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            format!("malformed .fai line: {}", line),
+                        )
+                    })
+            };
+            let rec = FaiRecord {
+                name: cols[0].to_string(),
+                len: parse(1)?,
+                offset: parse(2)?,
+                line_bases: parse(3)?,
+                line_width: parse(4)?,
+            };
+            if (rec.line_bases == 0 && rec.len > 0) || rec.line_width < rec.line_bases {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("malformed .fai line: {}", line),
+                ));
+            }
+            index.push(rec)?;
+        }
+        Ok(index)
+    }
+    /// Write the index in `.fai` format.
+    pub fn write_fai(&self, fai_path: &str) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(fai_path)?);
+        for rec in &self.records {
+            writeln!(
+                writer,
+                "{}\t{}\t{}\t{}\t{}",
+                rec.name, rec.len, rec.offset, rec.line_bases, rec.line_width
+            )?;
+        }
+        writer.flush()
+    }
+    /// Load `<fa_path>.fai` if it exists. Otherwise build the index and write it to `<fa_path>.fai`.
+    pub fn load_or_build(fa_path: &str) -> io::Result<Self> {
+        let fai_path = format!("{}.fai", fa_path);
+        if Path::new(&fai_path).exists() {
+            Self::from_fai(&fai_path)
+        } else {
+            let index = Self::build(fa_path)?;
+            index.write_fai(&fai_path)?;
+            Ok(index)
+        }
+    }
+    pub fn get(&self, name: &str) -> Option<&FaiRecord> {
+        self.name_to_idx.get(name).map(|&i| &self.records[i])
+    }
+    pub fn records(&self) -> &[FaiRecord] {
+        &self.records
+    }
+}
+
+/// FASTA file with random access by a `.fai` index. The index is kept for repeated lookups.
+///
+/// A BGZF-compressed FASTA (`.gz`) is accessed by its `.gzi` block index as well,
+/// so that only the blocks covering the requested region are decompressed.
+pub struct IndexedFasta {
+    reader: FaSource,
+    index: FaIndex,
+}
+enum FaSource {
+    Plain(BufReader<File>),
+    Bgzf(BgzfReader),
+}
+impl IndexedFasta {
+    /// Open a FASTA file with `<fa_path>.fai` (built if it does not exist).
+    /// A `.gz` file must be BGZF-compressed and is opened with `<fa_path>.gzi` (built if it does not exist).
+    pub fn open(fa_path: &str) -> io::Result<Self> {
+        let index = FaIndex::load_or_build(fa_path)?;
+        let file = File::open(fa_path)?;
+        if fa_path.ends_with(".gz") {
+            let gzi = GziIndex::load_or_build(fa_path)?;
+            Ok(Self::with_bgzf_index(file, index, gzi))
+        } else {
+            Ok(Self::with_index(file, index))
+        }
+    }
+    pub fn with_index(file: File, index: FaIndex) -> Self {
+        IndexedFasta {
+            reader: FaSource::Plain(BufReader::new(file)),
+            index,
+        }
+    }
+    pub fn with_bgzf_index(file: File, index: FaIndex, gzi: GziIndex) -> Self {
+        IndexedFasta {
+            reader: FaSource::Bgzf(BgzfReader::new(file, gzi)),
+            index,
+        }
+    }
+    pub fn index(&self) -> &FaIndex {
+        &self.index
+    }
+    /// Fetch the bases in `[start, end)` (0-based) of the sequence `name`.
+    /// `end` is clipped to the length of the sequence.
+    ///
+    /// # Errors
+    ///
+    /// Return an error if `name` is not in the index or the region is empty.
+    pub fn fetch(&mut self, name: &str, start: u64, end: u64) -> io::Result<Vec<u8>> {
+        let rec = self.index.get(name).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("sequence '{}' not found in the index", name),
+            )
+        })?;
+        let end = end.min(rec.len);
+        if start >= end {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("invalid region {}:{}-{}", name, start + 1, end),
+            ));
+        }
+        let byte_start = rec.pos_to_offset(start);
+        let byte_end = rec.pos_to_offset(end - 1) + 1;
+        let mut buf = vec![0; (byte_end - byte_start) as usize];
+        match &mut self.reader {
+            FaSource::Plain(reader) => {
+                reader.seek(SeekFrom::Start(byte_start))?;
+                reader.read_exact(&mut buf)?;
+            }
+            FaSource::Bgzf(reader) => reader.read_at(byte_start, &mut buf)?,
+        }
+        buf.retain(|&c| c != b'\n' && c != b'\r');
+        Ok(buf)
+    }
+    /// Fetch a region written as `name`, `name:start` or `name:start-end` (1-based, inclusive).
+    pub fn fetch_region(&mut self, region: &str) -> io::Result<Vec<u8>> {
+        let (name, start, end) = parse_region(region, &self.index)?;
+        self.fetch(&name, start, end)
+    }
+}
+
+/// Parse `name`, `name:start` or `name:start-end` (1-based, inclusive; commas are allowed in numbers).
+/// Return the name and the 0-based half-open interval. A name containing `:` is matched as a whole first.
+pub fn parse_region(region: &str, index: &FaIndex) -> io::Result<(String, u64, u64)> {
+    if let Some(rec) = index.get(region) {
+        return Ok((rec.name.clone(), 0, rec.len));
+    }
+    let invalid = || {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("invalid region: {}", region),
+        )
+    };
+    let parse = |s: &str| s.replace(',', "").parse::<u64>().map_err(|_| invalid());
+    let (name, range) = region.rsplit_once(':').ok_or_else(invalid)?;
+    let (start, end) = match range.split_once('-') {
+        Some((s, e)) => (parse(s)?, parse(e)?),
+        None => (parse(range)?, u64::MAX),
+    };
+    if start == 0 || start > end {
+        return Err(invalid());
+    }
+    Ok((name.to_string(), start - 1, end))
+}
+
+/// Build `<fa_path>.fai` if no region is given. Otherwise output the regions to [`std::io::stdout()`] in FASTA format.
+/// For a BGZF-compressed FASTA (`.gz`), `<fa_path>.gzi` is built (or used) as well.
+///
+/// # Arguments
+///
+/// * `fa_path` - FASTA path
+/// * `regions` - Regions written as `name`, `name:start` or `name:start-end` (1-based, inclusive)
+///
+/// # Errors
+///
+/// Return an error if the operation cannot be completed.
+pub fn faidx(fa_path: &str, regions: &[String]) -> io::Result<()> {
+    if regions.is_empty() {
+        if fa_path.ends_with(".gz") {
+            GziIndex::build(fa_path)?.write_gzi(&format!("{}.gzi", fa_path))?;
+        }
+        let index = FaIndex::build(fa_path)?;
+        return index.write_fai(&format!("{}.fai", fa_path));
+    }
+    let mut fasta = IndexedFasta::open(fa_path)?;
+    let mut fx_writer = FxWriter::new(true);
+    for region in regions {
+        let seq = fasta.fetch_region(region)?;
+        fx_writer.write(region, &seq, None, &[])?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_index() {
+        let fa = b">chr1 desc\nACGTA\nCGTAC\nGT\n>chr2\r\nAAAA\r\nCC\r\n";
+        let index = FaIndex::build_from_reader(&fa[..]).unwrap();
+        assert_eq!(
+            index.records(),
+            &[
+                FaiRecord {
+                    name: "chr1".to_string(),
+                    len: 12,
+                    offset: 11,
+                    line_bases: 5,
+                    line_width: 6,
+                },
+                FaiRecord {
+                    name: "chr2".to_string(),
+                    len: 6,
+                    offset: 33,
+                    line_bases: 4,
+                    line_width: 6,
+                },
+            ]
+        );
+        let fa = b">chr1\nACG\nACGT\nA\n";
+        assert!(FaIndex::build_from_reader(&fa[..]).is_err());
+        // blank lines at the end of a sequence are allowed
+        let fa = b">chr1\nACGT\nAC\n\n>chr2\nAC\n\n";
+        let index = FaIndex::build_from_reader(&fa[..]).unwrap();
+        assert_eq!(index.records()[0].len, 6);
+        assert_eq!(index.records()[1].offset, 21);
+    }
+
+    #[test]
+    fn test_build_index_malformed() {
+        // a blank line followed by more sequence lines
+        let fa = b">chr1\nACGT\n\nACGT\n";
+        assert!(FaIndex::build_from_reader(&fa[..]).is_err());
+        let fa = b">chr1\n\nACGT\nACGT\n";
+        assert!(FaIndex::build_from_reader(&fa[..]).is_err());
+        // the last line longer than the others
+        let fa = b">chr1\nACGT\nACGTA\n>chr2\nAC\n";
+        assert!(FaIndex::build_from_reader(&fa[..]).is_err());
+        let fa = b">chr1\nACGT\nACGT\nACGTAC";
+        assert!(FaIndex::build_from_reader(&fa[..]).is_err());
+    }
+
+    #[test]
+    fn test_from_fai() {
+        let fai = b"chr1\t20\t6\t10\t11\nchr2\t0\t35\t0\t0\n";
+        let index = FaIndex::from_fai_reader(&fai[..]).unwrap();
+        assert_eq!(index.get("chr1").unwrap().pos_to_offset(12), 6 + 11 + 2);
+        assert_eq!(index.get("chr2").unwrap().len, 0);
+        // no bases per line, fewer bytes than bases per line, and a missing column
+        assert!(FaIndex::from_fai_reader(&b"chr1\t20\t6\t0\t21\n"[..]).is_err());
+        assert!(FaIndex::from_fai_reader(&b"chr1\t20\t6\t10\t9\n"[..]).is_err());
+        assert!(FaIndex::from_fai_reader(&b"chr1\t20\t6\t10\n"[..]).is_err());
+    }
+
+    #[test]
+    fn test_parse_region() {
+        let fa = b">chr1\nACGTACGTAC\n>chr:2\nACGT\n";
+        let index = FaIndex::build_from_reader(&fa[..]).unwrap();
+        assert_eq!(
+            parse_region("chr1", &index).unwrap(),
+            ("chr1".to_string(), 0, 10)
+        );
+        assert_eq!(
+            parse_region("chr1:2-5", &index).unwrap(),
+            ("chr1".to_string(), 1, 5)
+        );
+        assert_eq!(
+            parse_region("chr1:1,001", &index).unwrap(),
+            ("chr1".to_string(), 1000, u64::MAX)
+        );
+        assert_eq!(
+            parse_region("chr:2", &index).unwrap(),
+            ("chr:2".to_string(), 0, 4)
+        );
+        assert!(parse_region("chr1:5-2", &index).is_err());
+        assert!(parse_region("chr1:0-2", &index).is_err());
+    }
+}
@@ -0,0 +1,191 @@
+use crate::bed::{BedPos, Strand};
+use crate::dna;
+use crate::gff::{self, GffFeature};
+use crate::io_utils::{FaReader, FqReader, FxWriter};
+use crate::record::RecordType;
+use crate::sub_cli::GetfeatArgs;
+use std::collections::HashMap;
+
+/// Parses FASTA/Q file and extracts the features of a GFF3/GTF file by the feature type (e.g., `CDS`, `exon` or `gene`).
+/// Outputs the results to [`std::io::stdout()`] in FASTA/Q format.
+///
+/// Features are grouped by the parent (`Parent` of GFF3 or `transcript_id` of GTF), so that a multi-part CDS
+/// is joined into one sequence. Features on the minus strand are reverse-complemented.
+/// Each record is named by the attribute `name_attr` (e.g., `gene_name`), or by the group ID if the attribute is absent.
+/// The group ID is added as the comment if it differs from the name.
+///
+/// # Arguments
+///
+/// Check the arguments by `--help`
+///
+/// # Errors
+///
+/// Return an error if the operation cannot be completed.
+pub fn getfeat_fastx(
+    fx_path: &str,
+    gparas: &GetfeatArgs,
+    is_fasta: bool,
+) -> Result<(), std::io::Error> {
+    let ftype = gparas.feature_type.as_deref().unwrap_or("CDS");
+    let name_attr = gparas.name_attr.as_deref().unwrap_or("gene_name");
+    let groups = FeatureGroups::from(&gparas.gff, ftype, name_attr)?;
+    let mut fx_writer = FxWriter::new(is_fasta);
+    if is_fasta {
+        let fa_iter = FaReader::new(fx_path)?;
+        for record in fa_iter.records() {
+            match record {
+                Ok(read) => write_features(&mut fx_writer, &read, &groups, true)?,
+                Err(e) => eprintln!("Error read FASTA: {}", e),
+            }
+        }
+    } else {
+        let fq_iter = FqReader::new(fx_path)?;
+        for record in fq_iter.records() {
+            match record {
+                Ok(read) => write_features(&mut fx_writer, &read, &groups, false)?,
+                Err(e) => eprintln!("Error read FASTQ: {}", e),
+            }
+        }
+    }
+    Ok(())
+}
+
+#[derive(Debug, PartialEq)]
+struct FeatureGroup {
+    id: String,
+    name: Option<String>,
+    strand: Strand,
+    parts: Vec<BedPos>,
+}
+
+/// Groups of features by the sequence name, in the order of their first appearance.
+struct FeatureGroups {
+    groups: Vec<FeatureGroup>,
+    by_seqid: HashMap<String, Vec<usize>>,
+    by_id: HashMap<(String, String), usize>, // {(seqid, group id): index}
+}
+impl FeatureGroups {
+    fn from(path: &str, ftype: &str, name_attr: &str) -> Result<Self, std::io::Error> {
+        let mut feature_groups = FeatureGroups {
+            groups: Vec::new(),
+            by_seqid: HashMap::new(),
+            by_id: HashMap::new(),
+        };
+        gff::read_gff(path, |feature, _| {
+            if feature.ftype == ftype {
+                feature_groups.add(&feature, name_attr);
+            }
+        })?;
+        feature_groups
+            .groups
+            .iter_mut()
+            .for_each(|g| g.parts.sort_unstable());
+        Ok(feature_groups)
+    }
+    fn add(&mut self, feature: &GffFeature, name_attr: &str) {
+        for id in feature.group_ids() {
+            let key = (feature.seqid.clone(), id.clone());
+            let idx = match self.by_id.get(&key) {
+                Some(&i) => i,
+                None => {
+                    let i = self.groups.len();
+                    self.groups.push(FeatureGroup {
+                        id,
+                        name: None,
+                        strand: feature.strand,
+                        parts: Vec::new(),
+                    });
+                    self.by_seqid
+                        .entry(feature.seqid.clone())
+                        .or_default()
+                        .push(i);
+                    self.by_id.insert(key, i);
+                    i
+                }
+            };
+            let group = &mut self.groups[idx];
+            if group.name.is_none() {
+                group.name = feature.attr(name_attr).map(|v| v.to_string());
+            }
+            group.parts.push(feature.pos);
+        }
+    }
+    fn get(&self, seqid: &str) -> impl Iterator<Item = &FeatureGroup> {
+        self.by_seqid
+            .get(seqid)
+            .into_iter()
+            .flatten()
+            .map(|&i| &self.groups[i])
+    }
+}
+
+fn write_features(
+    fx_writer: &mut FxWriter,
+    read: &dyn RecordType,
+    groups: &FeatureGroups,
+    is_fasta: bool,
+) -> Result<(), std::io::Error> {
+    let len = read.seq().len();
+    for group in groups.get(read.id()) {
+        let (mut seq, mut qual) = (Vec::new(), Vec::new());
+        for part in &group.parts {
+            let end = part.1.min(len);
+            if part.0 < end {
+                seq.extend_from_slice(&read.seq()[part.0..end]);
+                if !is_fasta {
+                    qual.extend_from_slice(&read.qual()[part.0..end]);
+                }
+            }
+        }
+        if seq.is_empty() {
+            continue;
+        }
+        if group.strand == Strand::Reverse {
+            dna::revcomp(&mut seq);
+            qual.reverse();
+        }
+        let name = group.name.as_deref().unwrap_or(&group.id);
+        let desc = if name == group.id {
+            None
+        } else {
+            Some(group.id.as_str())
+        };
+        fx_writer.write(name, &seq, desc, &qual)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_feature_groups() {
+        let mut groups = FeatureGroups {
+            groups: Vec::new(),
+            by_seqid: HashMap::new(),
+            by_id: HashMap::new(),
+        };
+        let cds = |start: usize, end: usize, parent: &str| GffFeature {
+            seqid: "chr1".to_string(),
+            ftype: "CDS".to_string(),
+            pos: BedPos(start, end),
+            strand: Strand::Reverse,
+            attributes: vec![
+                ("Parent".to_string(), parent.to_string()),
+                ("gene_name".to_string(), "ABC".to_string()),
+            ],
+            parents: parent.split(',').map(|p| p.to_string()).collect(),
+        };
+        groups.add(&cds(20, 30, "tx1"), "gene_name");
+        groups.add(&cds(0, 5, "tx1,tx2"), "gene_name");
+        groups.add(&cds(40, 45, "tx2"), "gene_name");
+        let result: Vec<&FeatureGroup> = groups.get("chr1").collect();
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].id, "tx1");
+        assert_eq!(result[0].name, Some("ABC".to_string()));
+        assert_eq!(result[0].parts, vec![BedPos(20, 30), BedPos(0, 5)]);
+        assert_eq!(result[1].parts, vec![BedPos(0, 5), BedPos(40, 45)]);
+        assert_eq!(groups.get("chr2").count(), 0);
+    }
+}
@@ -0,0 +1,230 @@
+use crate::bed::{BedFeature, BedPos, Strand};
+use crate::io_utils::buffer_reader_maybe_gz;
+use std::io::BufRead;
+
+/// One feature line of GFF3/GTF. Positions are converted to 0-based, half-open.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GffFeature {
+    pub seqid: String,
+    pub ftype: String,
+    pub pos: BedPos,
+    pub strand: Strand,
+    pub attributes: Vec<(String, String)>,
+    /// The IDs of `Parent` (GFF3), split at `,` before decoding the `%XX` escapes
+    pub parents: Vec<String>,
+}
+impl GffFeature {
+    pub fn attr(&self, key: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+    /// The groups this feature belongs to, i.e., `Parent` (GFF3, may be multiple) or `transcript_id` (GTF).
+    /// A feature without a parent forms its own group named by `ID` or `gene_id`.
+    pub fn group_ids(&self) -> Vec<String> {
+        if !self.parents.is_empty() {
+            return self.parents.clone();
+        }
+        match self
+            .attr("transcript_id")
+            .or(self.attr("ID"))
+            .or(self.attr("gene_id"))
+        {
+            Some(id) => vec![id.to_string()],
+            None => vec![format!("{}:{}-{}", self.seqid, self.pos.0 + 1, self.pos.1)],
+        }
+    }
+    pub fn to_bed_feature(&self) -> BedFeature {
+        let name = ["ID", "Name", "transcript_id", "gene_id"]
+            .iter()
+            .find_map(|&k| self.attr(k))
+            .map(|v| v.to_string());
+        BedFeature {
+            pos: self.pos,
+            name,
+            score: None,
+            strand: self.strand,
+            blocks: None,
+        }
+    }
+}
+
+/// Return true if the path looks like GFF3/GTF (`.gff`, `.gff3` or `.gtf`, optionally with `.gz`).
+pub fn is_gff_path(path: &str) -> bool {
+    let path = path.strip_suffix(".gz").unwrap_or(path);
+    path.ends_with(".gff") || path.ends_with(".gff3") || path.ends_with(".gtf")
+}
+
+/// Read GFF3/GTF lines and pass the features with their line numbers to `add` in file order.
+/// Comment lines are skipped and reading stops at the `##FASTA` section of GFF3.
+///
+/// # Errors
+///
+/// Return an error with the file name and the line number if a line is malformed.
+pub fn read_gff<F: FnMut(GffFeature, usize)>(path: &str, mut add: F) -> Result<(), std::io::Error> {
+    let reader = buffer_reader_maybe_gz(path)?;
+    for (i, line) in reader.lines().enumerate() {
+        match line {
+            Ok(line_content) => {
+                if line_content.starts_with("##FASTA") {
+                    break;
+                }
+                if line_content.trim().is_empty() || line_content.starts_with('#') {
+                    continue;
+                }
+                match parse_gff_line(&line_content) {
+                    Some(feature) => add(feature, i + 1),
+                    None => {
+                        return Err(std::io::Error::new(
+                            std::io::ErrorKind::InvalidData,
+                            format!("{}:{}: malformed GFF/GTF line", path, i + 1),
+                        ))
+                    }
+                }
+            }
+            Err(e) => eprintln!("Error reading line: {}", e),
+        }
+    }
+    Ok(())
+}
+
+fn parse_gff_line(line_content: &str) -> Option<GffFeature> {
+    let columns: Vec<&str> = line_content.split('\t').collect();
+    if columns.len() < 9 {
+        return None;
+    }
+    let start = columns[3].parse::<usize>().ok()?;
+    let end = columns[4].parse::<usize>().ok()?;
+    if start == 0 || start > end {
+        return None;
+    }
+    Some(GffFeature {
+        seqid: columns[0].to_string(),
+        ftype: columns[2].to_string(),
+        pos: BedPos(start - 1, end),
+        strand: Strand::from(columns[6]),
+        attributes: parse_attributes(columns[8]),
+        parents: parse_parents(columns[8]),
+    })
+}
+
+/// Parse GFF3 (`key=value;...`) or GTF (`key "value"; ...`) attributes.
+/// An attribute is GFF3 if `=` comes before the first space (except around `=`), e.g., `Note=see "x"`.
+fn parse_attributes(s: &str) -> Vec<(String, String)> {
+    split_attributes(s)
+        .filter_map(|a| match split_gff3_attribute(a) {
+            Some((k, v)) => Some((percent_decode(k.trim()), percent_decode(v.trim()))),
+            None => a
+                .split_once(' ')
+                .map(|(k, v)| (k.to_string(), v.trim().trim_matches('"').to_string())),
+        })
+        .collect()
+}
+/// Split the attributes at `;` outside double quotes (e.g., `gene_name "a;b"` is one attribute).
+fn split_attributes(s: &str) -> impl Iterator<Item = &str> {
+    let mut in_quotes = false;
+    s.split(move |c| {
+        if c == '"' {
+            in_quotes = !in_quotes;
+        }
+        c == ';' && !in_quotes
+    })
+    .map(|a| a.trim())
+    .filter(|a| !a.is_empty())
+}
+/// The raw key and value of a GFF3 attribute, or `None` for a GTF one.
+fn split_gff3_attribute(a: &str) -> Option<(&str, &str)> {
+    let (k, v) = a.split_once('=')?;
+    (!k.trim().contains(' ')).then_some((k, v))
+}
+/// Split the raw `Parent` value of GFF3 attributes at `,`, then decode each ID (e.g., `tx%2C1` is one parent).
+fn parse_parents(s: &str) -> Vec<String> {
+    split_attributes(s)
+        .filter_map(split_gff3_attribute)
+        .find(|(k, _)| percent_decode(k.trim()) == "Parent")
+        .map(|(_, v)| {
+            v.trim()
+                .split(',')
+                .filter(|p| !p.is_empty())
+                .map(percent_decode)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+/// Decode `%XX` escapes of GFF3.
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out: Vec<u8> = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+            if let Some(b) = hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                out.push(b);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_gff3_line() {
+        let f = parse_gff_line(
+            "chr1\t.\tCDS\t3\t10\t.\t-\t0\tID=cds1;Parent=tx1,tx2;gene_name=ABC%3B1",
+        )
+        .unwrap();
+        assert_eq!(f.seqid, "chr1");
+        assert_eq!(f.ftype, "CDS");
+        assert_eq!(f.pos, BedPos(2, 10));
+        assert_eq!(f.strand, Strand::Reverse);
+        assert_eq!(f.attr("gene_name"), Some("ABC;1"));
+        assert_eq!(f.group_ids(), vec!["tx1".to_string(), "tx2".to_string()]);
+        // an encoded comma is a part of the ID
+        let f = parse_gff_line("chr1\t.\tCDS\t3\t10\t.\t-\t0\tParent=tx%2C1,tx%3B2").unwrap();
+        assert_eq!(f.group_ids(), vec!["tx,1".to_string(), "tx;2".to_string()]);
+        // quotes in a GFF3 value
+        let f = parse_gff_line("chr1\t.\tCDS\t3\t10\t.\t-\t0\tID=cds1;Note=see \"x\";Parent=tx1")
+            .unwrap();
+        assert_eq!(f.attr("Note"), Some("see \"x\""));
+        assert_eq!(f.group_ids(), vec!["tx1".to_string()]);
+        assert!(parse_gff_line("chr1\t.\tCDS\t0\t10\t.\t-\t0\tID=cds1").is_none());
+        assert!(parse_gff_line("chr1\t.\tCDS\t3\t10").is_none());
+    }
+
+    #[test]
+    fn test_parse_gtf_line() {
+        let f = parse_gff_line(
+            "chr1\t.\texon\t1\t5\t.\t+\t.\tgene_id \"g1\"; transcript_id \"t1\"; gene_name \"A=B\"; exon_number 1;",
+        )
+        .unwrap();
+        assert_eq!(f.pos, BedPos(0, 5));
+        assert_eq!(f.attr("gene_id"), Some("g1"));
+        assert_eq!(f.attr("gene_name"), Some("A=B"));
+        assert_eq!(f.attr("exon_number"), Some("1"));
+        assert_eq!(f.group_ids(), vec!["t1".to_string()]);
+        assert_eq!(f.to_bed_feature().name, Some("t1".to_string()));
+        // a semicolon in a quoted value
+        let f = parse_gff_line(
+            "chr1\t.\texon\t1\t5\t.\t+\t.\tgene_id \"g1\"; gene_name \"a;b\"; transcript_id \"t1\";",
+        )
+        .unwrap();
+        assert_eq!(f.attr("gene_name"), Some("a;b"));
+        assert_eq!(f.attr("transcript_id"), Some("t1"));
+    }
+
+    #[test]
+    fn test_is_gff_path() {
+        assert!(is_gff_path("a.gff3"));
+        assert!(is_gff_path("a.gtf.gz"));
+        assert!(!is_gff_path("a.bed"));
+    }
+}
@@ -1,19 +1,33 @@
 use bio::io::{fasta, fastq};
-use flate2::read::GzDecoder;
+use flate2::read::MultiGzDecoder;
+use std::collections::HashSet;
 use std::fmt::Display;
 use std::fs::File;
-use std::io::{self, BufRead, BufReader, BufWriter, Stdout, Write};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
 
 pub fn buffer_reader_maybe_gz(path: &str) -> io::Result<Box<dyn BufRead>> {
     let file = File::open(path)?;
     if path.ends_with(".gz") {
-        let gz_decoder = GzDecoder::new(file);
+        // multi-member gzip (e.g., BGZF) has to be read to the last member
+        let gz_decoder = MultiGzDecoder::new(file);
         Ok(Box::new(BufReader::new(gz_decoder)))
     } else {
         Ok(Box::new(BufReader::new(file)))
     }
 }
 
+/// Read names from the first column of each line. Empty lines are ignored.
+pub fn read_name_set(path: &str) -> io::Result<HashSet<String>> {
+    let reader = buffer_reader_maybe_gz(path)?;
+    let mut names = HashSet::new();
+    for line in reader.lines() {
+        if let Some(name) = line?.split_whitespace().next() {
+            names.insert(name.to_string());
+        }
+    }
+    Ok(names)
+}
+
 pub struct FaReader(fasta::Reader<BufReader<Box<dyn BufRead>>>);
 pub struct FqReader(fastq::Reader<BufReader<Box<dyn BufRead>>>);
 impl FaReader {
@@ -50,16 +64,21 @@ impl Output {
 }
 
 pub enum FxWriter {
-    Fasta(fasta::Writer<Stdout>),
-    Fastq(fastq::Writer<Stdout>),
+    Fasta(fasta::Writer<Box<dyn Write>>),
+    Fastq(fastq::Writer<Box<dyn Write>>),
 }
 impl FxWriter {
     pub fn new(is_fasta: bool) -> FxWriter {
-        let std_out = io::stdout();
+        Self::from_writer(Box::new(io::stdout()), is_fasta)
+    }
+    pub fn from_path(path: &str, is_fasta: bool) -> io::Result<FxWriter> {
+        Ok(Self::from_writer(Box::new(File::create(path)?), is_fasta))
+    }
+    fn from_writer(out: Box<dyn Write>, is_fasta: bool) -> FxWriter {
         if is_fasta {
-            FxWriter::Fasta(fasta::Writer::new(std_out))
+            FxWriter::Fasta(fasta::Writer::new(out))
         } else {
-            FxWriter::Fastq(fastq::Writer::new(std_out))
+            FxWriter::Fastq(fastq::Writer::new(out))
         }
     }
     pub fn write(
@@ -1,12 +1,29 @@
+pub mod adapter;
+mod alias;
 mod bed;
+pub mod bed_ops;
+pub mod bgzf;
+pub mod cutn;
+pub mod dedup;
+pub mod demux;
 mod dna;
+pub mod faidx;
 pub mod fqchk;
+pub mod getfeat;
+mod gff;
 mod io_utils;
+pub mod locate;
+pub mod maskbed;
 pub mod nc_comp;
+pub mod primer;
 mod record;
+pub mod rename;
+mod rmsk;
 pub mod seq;
 pub mod size;
 mod stats;
 pub mod sub_cli;
 pub mod subsample;
+pub mod subseq;
 pub mod trim;
+pub mod umi;
@@ -0,0 +1,201 @@
+use crate::dna;
+use crate::io_utils::{FaReader, FqReader, Output};
+use crate::record::RecordType;
+use crate::sub_cli::LocateArgs;
+
+/// Parses FASTA/Q file and searches the motifs on both strands.
+/// Outputs the hits to [`std::io::stdout()`] in BED6 format:
+/// - `chrom`: Sequence name
+/// - `start`, `end`: Position of the hit on the forward strand (0-based, half-open)
+/// - `name`: Motif name
+/// - `score`: Number of mismatches
+/// - `strand`: `+` or `-`
+///
+/// Motifs may contain IUPAC degenerate codes. A base of the sequence matches a motif base
+/// if every nucleotide it stands for is allowed by the motif base (e.g., `A` matches `R`, but `N` matches only `N`).
+/// The reverse strand is not searched for a motif equal to its own reverse complement (e.g., `GAATTC`).
+///
+/// # Arguments
+///
+/// Check the arguments by `--help`
+///
+/// # Errors
+///
+/// Return an error if the operation cannot be completed.
+pub fn locate_fastx(
+    fx_path: &str,
+    lparas: &LocateArgs,
+    is_fasta: bool,
+) -> Result<(), std::io::Error> {
+    let motifs = load_motifs(lparas)?;
+    let max_mismatches = lparas.max_mismatches.unwrap_or(0);
+    let mut output = Output::new();
+    if is_fasta {
+        let fa_iter = FaReader::new(fx_path)?;
+        for record in fa_iter.records() {
+            match record {
+                Ok(read) => print_hits(&mut output, &read, &motifs, max_mismatches)?,
+                Err(e) => eprintln!("Error read FASTA: {}", e),
+            }
+        }
+    } else {
+        let fq_iter = FqReader::new(fx_path)?;
+        for record in fq_iter.records() {
+            match record {
+                Ok(read) => print_hits(&mut output, &read, &motifs, max_mismatches)?,
+                Err(e) => eprintln!("Error read FASTQ: {}", e),
+            }
+        }
+    }
+    Ok(())
+}
+
+pub(crate) struct Motif {
+    pub(crate) name: String,
+    /// IUPAC masks of the forward motif
+    pub(crate) fwd: Vec<u8>,
+    /// IUPAC masks of the reverse complement (`None` if it equals the forward motif)
+    pub(crate) rev: Option<Vec<u8>>,
+}
+impl Motif {
+    pub(crate) fn new(name: &str, seq: &[u8]) -> Result<Self, std::io::Error> {
+        let mut seq = seq.to_ascii_uppercase();
+        if seq.is_empty() || seq.iter().any(|&b| dna::get_iupac_mask(b) == 0) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!(
+                    "invalid motif '{}': only IUPAC nucleotide codes are allowed",
+                    name
+                ),
+            ));
+        }
+        let fwd: Vec<u8> = seq.iter().map(|&b| dna::get_iupac_mask(b)).collect();
+        dna::revcomp(&mut seq);
+        let rev: Vec<u8> = seq.iter().map(|&b| dna::get_iupac_mask(b)).collect();
+        Ok(Motif {
+            name: name.to_string(),
+            rev: if rev == fwd { None } else { Some(rev) },
+            fwd,
+        })
+    }
+}
+
+fn load_motifs(lparas: &LocateArgs) -> Result<Vec<Motif>, std::io::Error> {
+    let mut motifs = Vec::new();
+    for m in &lparas.motif {
+        motifs.push(Motif::new(m, m.as_bytes())?);
+    }
+    if let Some(path) = &lparas.motif_fa {
+        let fa_iter = FaReader::new(path)?;
+        for record in fa_iter.records() {
+            let record = record
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e.to_string()))?;
+            motifs.push(Motif::new(record.id(), record.seq())?);
+        }
+    }
+    Ok(motifs)
+}
+
+fn print_hits(
+    output: &mut Output,
+    read: &dyn RecordType,
+    motifs: &[Motif],
+    max_mismatches: usize,
+) -> Result<(), std::io::Error> {
+    let seq: Vec<u8> = read.seq().iter().map(|&b| dna::get_iupac_mask(b)).collect();
+    for motif in motifs {
+        let mut hits: Vec<(usize, usize, char)> = find_hits(&seq, &motif.fwd, max_mismatches)
+            .into_iter()
+            .map(|(pos, mm)| (pos, mm, '+'))
+            .collect();
+        if let Some(rev) = &motif.rev {
+            hits.extend(
+                find_hits(&seq, rev, max_mismatches)
+                    .into_iter()
+                    .map(|(pos, mm)| (pos, mm, '-')),
+            );
+            hits.sort_unstable();
+        }
+        for (pos, mm, strand) in hits {
+            output.write(format!(
+                "{}\t{}\t{}\t{}\t{}\t{}\n",
+                read.id(),
+                pos,
+                pos + motif.fwd.len(),
+                motif.name,
+                mm,
+                strand
+            ))?;
+        }
+    }
+    Ok(())
+}
+
+/// Return `(start, mismatches)` of every window of `seq` that matches `motif` with at most `max_mismatches`.
+/// Both `seq` and `motif` are IUPAC masks.
+pub(crate) fn find_hits(seq: &[u8], motif: &[u8], max_mismatches: usize) -> Vec<(usize, usize)> {
+    let mut hits = Vec::new();
+    if motif.len() > seq.len() {
+        return hits;
+    }
+    for (pos, window) in seq.windows(motif.len()).enumerate() {
+        let mut mm = 0;
+        for (&s, &m) in window.iter().zip(motif) {
+            if s == 0 || s & !m != 0 {
+                mm += 1;
+                if mm > max_mismatches {
+                    break;
+                }
+            }
+        }
+        if mm <= max_mismatches {
+            hits.push((pos, mm));
+        }
+    }
+    hits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn to_mask(s: &[u8]) -> Vec<u8> {
+        s.iter().map(|&b| dna::get_iupac_mask(b)).collect()
+    }
+
+    #[test]
+    fn test_find_hits() {
+        let seq = to_mask(b"ACGTTGAATTCaNGGATCC");
+        // exact
+        assert_eq!(find_hits(&seq, &to_mask(b"GAATTC"), 0), vec![(5, 0)]);
+        // degenerate: GGATCC / GAATTC by GRAWYC
+        assert_eq!(
+            find_hits(&seq, &to_mask(b"GRAWYC"), 0),
+            vec![(5, 0), (13, 0)]
+        );
+        // N in the motif matches any base, but N in the sequence matches only N
+        assert_eq!(find_hits(&seq, &to_mask(b"ANG"), 0), vec![(0, 0), (11, 0)]);
+        assert_eq!(find_hits(&seq, &to_mask(b"AAG"), 0), vec![]);
+        assert_eq!(
+            find_hits(&seq, &to_mask(b"AAG"), 1),
+            vec![(0, 1), (6, 1), (11, 1)]
+        );
+        // mismatches
+        assert_eq!(find_hits(&seq, &to_mask(b"GGATCA"), 1), vec![(13, 1)]);
+        assert_eq!(
+            find_hits(&seq, &to_mask(b"ACGTTGAATTCaNGGATCCA"), 5),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn test_motif() {
+        let m = Motif::new("EcoRI", b"GAATTC").unwrap();
+        assert!(m.rev.is_none());
+        let m = Motif::new("m1", b"acgR").unwrap();
+        assert_eq!(m.fwd, to_mask(b"ACGR"));
+        assert_eq!(m.rev, Some(to_mask(b"YCGT")));
+        assert!(Motif::new("bad", b"ACGX").is_err());
+        assert!(Motif::new("empty", b"").is_err());
+    }
+}
@@ -0,0 +1,128 @@
+use crate::bed::BedPos;
+use crate::dna::SeqComp;
+use crate::io_utils::{FaReader, FqReader, Output};
+use crate::record::RecordType;
+use crate::sub_cli::{MaskClass, MaskbedArgs};
+
+/// Parses FASTA/Q file and reports the runs of the chosen character classes as BED3 (0-based, half-open).
+/// Outputs the results to [`std::io::stdout()`].
+///
+/// The classes are those of [`SeqComp`]: lowercase (soft-masked) bases, `N`, two-base (`RYSWKM`) and three-base (`BDHV`)
+/// IUPAC codes, and other characters. A run consists of the bases in any of the chosen classes, and runs shorter than
+/// `--min-len` are not reported (e.g., `-c n -l 10` reports the N gaps of at least 10 bases).
+///
+/// # Arguments
+///
+/// Check the arguments by `--help`
+///
+/// # Errors
+///
+/// Return an error if the operation cannot be completed.
+pub fn maskbed_fastx(
+    fx_path: &str,
+    mparas: &MaskbedArgs,
+    is_fasta: bool,
+) -> Result<(), std::io::Error> {
+    let selected = get_selected_indexes(&mparas.class);
+    let min_len = mparas.min_len.unwrap_or(1);
+    let mut output = Output::new();
+    if is_fasta {
+        let fa_iter = FaReader::new(fx_path)?;
+        for record in fa_iter.records() {
+            match record {
+                Ok(read) => print_runs(&mut output, &read, &selected, min_len)?,
+                Err(e) => eprintln!("Error read FASTA: {}", e),
+            }
+        }
+    } else {
+        let fq_iter = FqReader::new(fx_path)?;
+        for record in fq_iter.records() {
+            match record {
+                Ok(read) => print_runs(&mut output, &read, &selected, min_len)?,
+                Err(e) => eprintln!("Error read FASTQ: {}", e),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The IUPAC indexes of [`SeqComp`] (0-14) selected by the classes. Lowercase is used if no class is given.
+pub(crate) fn get_selected_indexes(classes: &[MaskClass]) -> [bool; 15] {
+    let mut selected = [false; 15];
+    let classes = if classes.is_empty() {
+        &[MaskClass::Lower][..]
+    } else {
+        classes
+    };
+    for class in classes {
+        let indexes: &[usize] = match class {
+            MaskClass::Lower => &[7, 8, 9, 10, 11, 12, 13],
+            MaskClass::N => &[6, 13],
+            MaskClass::Iupac2 => &[4, 11],
+            MaskClass::Iupac3 => &[5, 12],
+            MaskClass::Other => &[14],
+        };
+        indexes.iter().for_each(|&i| selected[i] = true);
+    }
+    selected
+}
+
+fn print_runs(
+    output: &mut Output,
+    read: &dyn RecordType,
+    selected: &[bool; 15],
+    min_len: usize,
+) -> Result<(), std::io::Error> {
+    for BedPos(start, end) in find_runs(read.seq(), selected, min_len) {
+        output.write(format!("{}\t{}\t{}\n", read.id(), start, end))?;
+    }
+    Ok(())
+}
+
+/// Return the runs of the bases whose IUPAC indexes are selected, with the length of at least `min_len`.
+pub(crate) fn find_runs(seq: &[u8], selected: &[bool; 15], min_len: usize) -> Vec<BedPos> {
+    let mut runs = Vec::new();
+    let mut start: Option<usize> = None;
+    for (i, &b) in seq.iter().enumerate() {
+        match (selected[SeqComp::get_iupac_index_from_u8(b)], start) {
+            (true, None) => start = Some(i),
+            (false, Some(s)) => {
+                if i - s >= min_len {
+                    runs.push(BedPos(s, i));
+                }
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        if seq.len() - s >= min_len {
+            runs.push(BedPos(s, seq.len()));
+        }
+    }
+    runs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_runs() {
+        let seq = b"acGTNNNnnACRyTTxx";
+        let lower = get_selected_indexes(&[]);
+        assert_eq!(
+            find_runs(seq, &lower, 1),
+            vec![BedPos(0, 2), BedPos(7, 9), BedPos(12, 13)]
+        );
+        let n = get_selected_indexes(&[MaskClass::N]);
+        assert_eq!(find_runs(seq, &n, 1), vec![BedPos(4, 9)]);
+        assert_eq!(find_runs(seq, &n, 6), vec![]);
+        let iupac2 = get_selected_indexes(&[MaskClass::Iupac2, MaskClass::Other]);
+        assert_eq!(
+            find_runs(seq, &iupac2, 2),
+            vec![BedPos(11, 13), BedPos(15, 17)]
+        );
+        assert_eq!(find_runs(b"", &n, 1), vec![]);
+    }
+}
@@ -1,4 +1,5 @@
-use crate::bed::BedMap;
+use crate::alias::NameAlias;
+use crate::bed::{BedMap, BedNameCheck};
 use crate::dna::SeqComp;
 use crate::io_utils::{FaReader, FqReader, Output};
 use crate::record::RecordType;
@@ -112,6 +113,9 @@ pub fn calc_fa_comp_wo_bed(path: &str, exclude_masked: bool) -> Result<(), std::
 /// * `path` - FASTA path
 /// * `bed` - BED path
 /// * `exclude_masked` - If true, masked sequences (e.g., lowercases or char other then IUPAC code) will be excluded from the output.
+/// * `strict_bed` - If true, fail if a sequence name of the BED matches no record, or an interval extends beyond the record.
+/// * `name_alias` - Two-column file of sequence name aliases to match the BED names with the records.
+/// * `chr_alias` - If true, match the BED names with and without the `chr` prefix (and `chrM` with `MT`).
 ///
 /// # Errors
 ///
@@ -120,14 +124,19 @@ pub fn calc_fq_comp_with_bed(
     path: &str,
     bed: &str,
     exclude_masked: bool,
+    strict_bed: bool,
+    name_alias: Option<&str>,
+    chr_alias: bool,
 ) -> Result<(), std::io::Error> {
     let fq_iter = FqReader::new(path)?;
     let mut output = Output::new();
+    let bedmap = BedMap::from(bed)?.with_alias(NameAlias::from(name_alias, chr_alias)?);
+    let mut name_check = BedNameCheck::new(bed, strict_bed);
     if exclude_masked {
-        let bedmap = BedMap::from(bed)?;
         for record in fq_iter.records() {
             match record {
                 Ok(read) => {
+                    name_check.check(read.id(), bedmap.max_end(read.id()), read.seq().len())?;
                     if let Some(result) = cal_unmasked_seq_with_bed(&read, &bedmap) {
                         print(&mut output, read.id(), read.seq().len(), &result)?;
                     }
@@ -136,10 +145,10 @@ pub fn calc_fq_comp_with_bed(
             }
         }
     } else {
-        let bedmap = BedMap::from(bed)?;
         for record in fq_iter.records() {
             match record {
                 Ok(read) => {
+                    name_check.check(read.id(), bedmap.max_end(read.id()), read.seq().len())?;
                     if let Some(result) = cal_all_seq_with_bed(&read, &bedmap) {
                         print(&mut output, read.id(), read.seq().len(), &result)?;
                     }
@@ -148,6 +157,7 @@ pub fn calc_fq_comp_with_bed(
             }
         }
     }
+    name_check.finish(bedmap.iter().map(|(name, _)| name))?;
     Ok(())
 }
 /// Parses FASTQ file and compute the statistic w/o masked sequences with a BED file.
@@ -167,6 +177,9 @@ pub fn calc_fq_comp_with_bed(
 /// * `path` - FASTQ path
 /// * `bed` - BED path
 /// * `exclude_masked` - If true, masked sequences (e.g., lowercases or char other then IUPAC code) will be excluded from the output.
+/// * `strict_bed` - If true, fail if a sequence name of the BED matches no record, or an interval extends beyond the record.
+/// * `name_alias` - Two-column file of sequence name aliases to match the BED names with the records.
+/// * `chr_alias` - If true, match the BED names with and without the `chr` prefix (and `chrM` with `MT`).
 ///
 /// # Errors
 ///
@@ -175,14 +188,19 @@ pub fn calc_fa_comp_with_bed(
     path: &str,
     bed: &str,
     exclude_masked: bool,
+    strict_bed: bool,
+    name_alias: Option<&str>,
+    chr_alias: bool,
 ) -> Result<(), std::io::Error> {
     let fa_iter = FaReader::new(path)?;
     let mut output = Output::new();
+    let bedmap = BedMap::from(bed)?.with_alias(NameAlias::from(name_alias, chr_alias)?);
+    let mut name_check = BedNameCheck::new(bed, strict_bed);
     if exclude_masked {
-        let bedmap = BedMap::from(bed)?;
         for record in fa_iter.records() {
             match record {
                 Ok(read) => {
+                    name_check.check(read.id(), bedmap.max_end(read.id()), read.seq().len())?;
                     if let Some(result) = cal_unmasked_seq_with_bed(&read, &bedmap) {
                         print(&mut output, read.id(), read.seq().len(), &result)?;
                     }
@@ -191,10 +209,10 @@ pub fn calc_fa_comp_with_bed(
             }
         }
     } else {
-        let bedmap = BedMap::from(bed)?;
         for record in fa_iter.records() {
             match record {
                 Ok(read) => {
+                    name_check.check(read.id(), bedmap.max_end(read.id()), read.seq().len())?;
                     if let Some(result) = cal_all_seq_with_bed(&read, &bedmap) {
                         print(&mut output, read.id(), read.seq().len(), &result)?;
                     }
@@ -203,6 +221,7 @@ pub fn calc_fa_comp_with_bed(
             }
         }
     }
+    name_check.finish(bedmap.iter().map(|(name, _)| name))?;
     Ok(())
 }
 fn cal_all_seq<T: RecordType>(read: &T) -> [usize; 9] {
@@ -219,7 +238,10 @@ fn cal_all_seq_with_bed<T: RecordType>(read: &T, bedmap: &BedMap) -> Option<[usi
     let mut count: [usize; 23] = [0; 23];
     if let Some(bedvec) = bedmap.get(read.id()) {
         bedvec.iter().for_each(|pos| {
-            SeqComp::count_all_nc(&mut count, read.seq(), pos.0, pos.1);
+            let end = pos.1.min(read.seq().len());
+            if pos.0 < end {
+                SeqComp::count_all_nc(&mut count, read.seq(), pos.0, end);
+            }
         });
         let result = SeqComp::get_all_result(&count);
         return Some(result);
@@ -230,7 +252,10 @@ fn cal_unmasked_seq_with_bed<T: RecordType>(read: &T, bedmap: &BedMap) -> Option
     let mut count: [usize; 23] = [0; 23];
     if let Some(bedvec) = bedmap.get(read.id()) {
         bedvec.iter().for_each(|pos| {
-            SeqComp::count_unmasked_nc(&mut count, read.seq(), pos.0, pos.1);
+            let end = pos.1.min(read.seq().len());
+            if pos.0 < end {
+                SeqComp::count_unmasked_nc(&mut count, read.seq(), pos.0, end);
+            }
         });
         let result = SeqComp::get_unmasked_result(&count);
         return Some(result);
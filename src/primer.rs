@@ -0,0 +1,214 @@
+use crate::dna;
+use crate::io_utils::{FaReader, FqReader, FxWriter};
+use crate::locate::{find_hits, Motif};
+use crate::record::RecordType;
+use crate::sub_cli::TrimPrimerArgs;
+
+/// Parses FASTA/Q file, trims the PCR primers and orients the reads by the primer pairs.
+/// Outputs the reads to [`std::io::stdout()`] and the number of reads per primer pair to [`std::io::stderr()`].
+///
+/// (1) The forward and the reverse primers of all pairs are matched at the 5' end with IUPAC codes and mismatches.
+/// (2) The primer is cut, as well as the reverse complement of the other primer of the pair if it occurs later.
+/// (3) Reads starting with the reverse primer are reverse-complemented to the forward orientation.
+/// (4) `primer=<pair> strand=<+|->` is appended to the header.
+///
+/// # Arguments
+///
+/// Check the arguments by `--help`
+///
+/// # Errors
+///
+/// Return an error if the primers are invalid or the operation cannot be completed.
+pub fn trim_primer_fastx(
+    fx_path: &str,
+    pparas: &TrimPrimerArgs,
+    is_fasta: bool,
+) -> Result<(), std::io::Error> {
+    let pairs = load_primer_pairs(&pparas.primer_fa)?;
+    let max_mismatches = pparas.max_mismatches.unwrap_or(2);
+    let mut writer = FxWriter::new(is_fasta);
+    // reads per pair: (forward, reverse)
+    let mut counts = vec![(0, 0); pairs.len()];
+    let (mut total, mut untrimmed) = (0, 0);
+    let mut trim_record = |read: &dyn RecordType| -> Result<(), std::io::Error> {
+        total += 1;
+        let masks: Vec<u8> = read.seq().iter().map(|&b| dna::get_iupac_mask(b)).collect();
+        let Some((idx, is_reverse, start, end)) = match_primers(&masks, &pairs, max_mismatches)
+        else {
+            untrimmed += 1;
+            if pparas.discard_untrimmed {
+                return Ok(());
+            }
+            let qual = if is_fasta { &[][..] } else { read.qual() };
+            return writer.write(read.id(), read.seq(), read.desc(), qual);
+        };
+        let mut seq = read.seq()[start..end].to_vec();
+        let mut qual = if is_fasta {
+            Vec::new()
+        } else {
+            read.qual()[start..end].to_vec()
+        };
+        if is_reverse {
+            dna::revcomp(&mut seq);
+            qual.reverse();
+            counts[idx].1 += 1;
+        } else {
+            counts[idx].0 += 1;
+        }
+        let tag = format!(
+            "primer={} strand={}",
+            pairs[idx].name,
+            if is_reverse { '-' } else { '+' }
+        );
+        let desc = match read.desc() {
+            Some(desc) => format!("{} {}", desc, tag),
+            None => tag,
+        };
+        writer.write(read.id(), &seq, Some(&desc), &qual)
+    };
+    if is_fasta {
+        let fa_iter = FaReader::new(fx_path)?;
+        for record in fa_iter.records() {
+            match record {
+                Ok(read) => trim_record(&read)?,
+                Err(e) => eprintln!("Error read FASTA: {}", e),
+            }
+        }
+    } else {
+        let fq_iter = FqReader::new(fx_path)?;
+        for record in fq_iter.records() {
+            match record {
+                Ok(read) => trim_record(&read)?,
+                Err(e) => eprintln!("Error read FASTQ: {}", e),
+            }
+        }
+    }
+    eprintln!(
+        "Reads: {}, with primers: {}, without primers{}: {}",
+        total,
+        total - untrimmed,
+        if pparas.discard_untrimmed {
+            " (discarded)"
+        } else {
+            ""
+        },
+        untrimmed
+    );
+    eprintln!("#primer_pair\tforward\treverse");
+    for (pair, (fwd, rev)) in pairs.iter().zip(&counts) {
+        eprintln!("{}\t{}\t{}", pair.name, fwd, rev);
+    }
+    Ok(())
+}
+
+struct PrimerPair {
+    name: String,
+    fwd: Motif,
+    rev: Motif,
+}
+
+/// Read the primer pairs (forward, then reverse) from FASTA. A pair is named by its forward primer.
+fn load_primer_pairs(path: &str) -> Result<Vec<PrimerPair>, std::io::Error> {
+    let mut primers = Vec::new();
+    for record in FaReader::new(path)?.records() {
+        let record = record
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e.to_string()))?;
+        primers.push(Motif::new(record.id(), record.seq())?);
+    }
+    if primers.is_empty() || primers.len() % 2 != 0 {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!(
+                "{}: primers must be given in pairs (forward, then reverse), but {} found",
+                path,
+                primers.len()
+            ),
+        ));
+    }
+    let mut pairs = Vec::new();
+    let mut primers = primers.into_iter();
+    while let (Some(fwd), Some(rev)) = (primers.next(), primers.next()) {
+        pairs.push(PrimerPair {
+            name: fwd.name.clone(),
+            fwd,
+            rev,
+        });
+    }
+    Ok(pairs)
+}
+
+/// Mismatches of the primer at the 5' end of the read (IUPAC masks).
+fn match_5p(masks: &[u8], primer: &[u8], max_mismatches: usize) -> Option<usize> {
+    let prefix = masks.get(..primer.len())?;
+    find_hits(prefix, primer, max_mismatches)
+        .first()
+        .map(|&(_, mm)| mm)
+}
+
+/// The best primer at the 5' end as `(pair index, is reverse, start, end)`, where `start..end` is the read without the primers.
+/// The end is at the reverse complement of the other primer of the pair (if found after the 5' primer).
+fn match_primers(
+    masks: &[u8],
+    pairs: &[PrimerPair],
+    max_mismatches: usize,
+) -> Option<(usize, bool, usize, usize)> {
+    let mut best: Option<(usize, usize, bool)> = None; // (mismatches, index, is reverse)
+    for (idx, pair) in pairs.iter().enumerate() {
+        for (is_reverse, primer) in [(false, &pair.fwd), (true, &pair.rev)] {
+            if let Some(mm) = match_5p(masks, &primer.fwd, max_mismatches) {
+                let is_better = match best {
+                    Some((best_mm, _, _)) => mm < best_mm,
+                    None => true,
+                };
+                if is_better {
+                    best = Some((mm, idx, is_reverse));
+                }
+            }
+        }
+    }
+    let (_, idx, is_reverse) = best?;
+    let (primer, other) = if is_reverse {
+        (&pairs[idx].rev, &pairs[idx].fwd)
+    } else {
+        (&pairs[idx].fwd, &pairs[idx].rev)
+    };
+    let start = primer.fwd.len();
+    let other_rc = other.rev.as_ref().unwrap_or(&other.fwd);
+    let end = find_hits(&masks[start..], other_rc, max_mismatches)
+        .first()
+        .map_or(masks.len(), |&(pos, _)| start + pos);
+    Some((idx, is_reverse, start, end))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn to_mask(s: &[u8]) -> Vec<u8> {
+        s.iter().map(|&b| dna::get_iupac_mask(b)).collect()
+    }
+
+    #[test]
+    fn test_match_primers() {
+        let pairs = vec![PrimerPair {
+            name: "amp1".to_string(),
+            fwd: Motif::new("amp1", b"GTGYCAGCMGCCGCGGTAA").unwrap(),
+            rev: Motif::new("amp1_R", b"GGACTACNVGGGTWTCTAAT").unwrap(),
+        }];
+        // forward: F + insert + rc(R)
+        let read = b"GTGCCAGCAGCCGCGGTAAACGTACGTACGTATTAGAAACCCCTGTAGTCC";
+        assert_eq!(
+            match_primers(&to_mask(read), &pairs, 0),
+            Some((0, false, 19, 31))
+        );
+        // reverse: R (one mismatch) + rc(insert), without rc(F)
+        let read = b"GGACTACATGGGTATCTAATTTTTTT";
+        assert_eq!(match_primers(&to_mask(read), &pairs, 0), None);
+        assert_eq!(
+            match_primers(&to_mask(read), &pairs, 1),
+            Some((0, true, 20, 26))
+        );
+        // too short for the primers
+        assert_eq!(match_primers(&to_mask(b"GTGCCAGC"), &pairs, 2), None);
+    }
+}
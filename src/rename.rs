@@ -0,0 +1,64 @@
+use crate::alias::{ChrStyle, NameAlias};
+use crate::io_utils::{FaReader, FqReader, FxWriter};
+use crate::record::RecordType;
+use crate::sub_cli::RenameArgs;
+
+/// Parses FASTA/Q file and renames the records by an alias table and/or the chromosome naming style.
+/// Outputs the results to [`std::io::stdout()`] in FASTA/Q format.
+///
+/// The table (`--name-alias`) maps the 1st column to the 2nd column, and then `--to-ucsc` (`1` to `chr1`, `MT` to `chrM`)
+/// or `--to-ensembl` (`chr1` to `1`, `chrM` to `MT`) is applied. This is the same mapping as BED lookups use.
+/// Records not in the table keep their names. The comments, sequences and qualities are kept as they are.
+///
+/// # Arguments
+///
+/// Check the arguments by `--help`
+///
+/// # Errors
+///
+/// Return an error if the operation cannot be completed.
+pub fn rename_fastx(
+    fx_path: &str,
+    rparas: &RenameArgs,
+    is_fasta: bool,
+) -> Result<(), std::io::Error> {
+    let alias = NameAlias::from(rparas.name_alias.as_deref(), false)?;
+    let chr_style = if rparas.to_ucsc {
+        Some(ChrStyle::Ucsc)
+    } else if rparas.to_ensembl {
+        Some(ChrStyle::Ensembl)
+    } else {
+        None
+    };
+    let mut fx_writer = FxWriter::new(is_fasta);
+    if is_fasta {
+        let fa_iter = FaReader::new(fx_path)?;
+        for record in fa_iter.records() {
+            match record {
+                Ok(read) => write_renamed(&mut fx_writer, &read, &alias, chr_style, true)?,
+                Err(e) => eprintln!("Error read FASTA: {}", e),
+            }
+        }
+    } else {
+        let fq_iter = FqReader::new(fx_path)?;
+        for record in fq_iter.records() {
+            match record {
+                Ok(read) => write_renamed(&mut fx_writer, &read, &alias, chr_style, false)?,
+                Err(e) => eprintln!("Error read FASTQ: {}", e),
+            }
+        }
+    }
+    Ok(())
+}
+
+fn write_renamed(
+    fx_writer: &mut FxWriter,
+    read: &dyn RecordType,
+    alias: &NameAlias,
+    chr_style: Option<ChrStyle>,
+    is_fasta: bool,
+) -> Result<(), std::io::Error> {
+    let qual: &[u8] = if is_fasta { &[] } else { read.qual() };
+    let name = alias.rename(read.id(), chr_style);
+    fx_writer.write(&name, read.seq(), read.desc(), qual)
+}
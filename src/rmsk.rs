@@ -0,0 +1,142 @@
+use crate::bed::{BedFeature, BedPos, Strand};
+use crate::io_utils::buffer_reader_maybe_gz;
+use std::io::BufRead;
+
+/// One row of a RepeatMasker `.out` table. Positions are converted to 0-based, half-open.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RmskRecord {
+    pub seqid: String,
+    pub pos: BedPos,
+    pub strand: Strand,
+    /// Matching repeat (e.g., `L1PA2` or `(CA)n`)
+    pub repeat: String,
+    /// Repeat class/family (e.g., `LINE/L1` or `Simple_repeat`)
+    pub class_family: String,
+    /// Smith-Waterman score
+    pub score: String,
+}
+impl RmskRecord {
+    /// Return true if the class/family is one of `classes`, which may be a whole class (e.g., `LINE` for `LINE/L1`).
+    pub fn is_in_classes(&self, classes: &[String]) -> bool {
+        classes.iter().any(|c| {
+            self.class_family == *c
+                || self
+                    .class_family
+                    .strip_prefix(c.as_str())
+                    .is_some_and(|rest| rest.starts_with('/'))
+        })
+    }
+    pub fn to_bed_feature(&self) -> BedFeature {
+        BedFeature {
+            pos: self.pos,
+            name: Some(self.repeat.clone()),
+            score: Some(self.score.clone()),
+            strand: self.strand,
+            blocks: None,
+        }
+    }
+}
+
+/// Return true if the path looks like a RepeatMasker table (`.out`, optionally with `.gz`).
+pub fn is_rmsk_path(path: &str) -> bool {
+    path.strip_suffix(".gz").unwrap_or(path).ends_with(".out")
+}
+
+/// Read a RepeatMasker `.out` table and pass the records with their line numbers to `add` in file order.
+/// The header lines (and any line not starting with a numeric score) are skipped.
+///
+/// # Errors
+///
+/// Return an error with the file name and the line number if a line is malformed.
+pub fn read_rmsk<F: FnMut(RmskRecord, usize)>(
+    path: &str,
+    mut add: F,
+) -> Result<(), std::io::Error> {
+    let reader = buffer_reader_maybe_gz(path)?;
+    for (i, line) in reader.lines().enumerate() {
+        match line {
+            Ok(line_content) => {
+                let is_record = line_content
+                    .split_whitespace()
+                    .next()
+                    .is_some_and(|score| score.parse::<f64>().is_ok());
+                if !is_record {
+                    continue;
+                }
+                match parse_rmsk_line(&line_content) {
+                    Some(record) => add(record, i + 1),
+                    None => {
+                        return Err(std::io::Error::new(
+                            std::io::ErrorKind::InvalidData,
+                            format!("{}:{}: malformed RepeatMasker line", path, i + 1),
+                        ))
+                    }
+                }
+            }
+            Err(e) => eprintln!("Error reading line: {}", e),
+        }
+    }
+    Ok(())
+}
+
+fn parse_rmsk_line(line_content: &str) -> Option<RmskRecord> {
+    let columns: Vec<&str> = line_content.split_whitespace().collect();
+    if columns.len() < 11 {
+        return None;
+    }
+    let start = columns[5].parse::<usize>().ok()?;
+    let end = columns[6].parse::<usize>().ok()?;
+    if start == 0 || start > end {
+        return None;
+    }
+    let strand = match columns[8] {
+        "+" => Strand::Forward,
+        "C" | "-" => Strand::Reverse,
+        _ => return None,
+    };
+    Some(RmskRecord {
+        seqid: columns[4].to_string(),
+        pos: BedPos(start - 1, end),
+        strand,
+        repeat: columns[9].to_string(),
+        class_family: columns[10].to_string(),
+        score: columns[0].to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rmsk_line() {
+        let r = parse_rmsk_line(
+            "  463   1.3  0.6  1.7  chr1        10001   10468 (248945954) +  (CCCTAA)n      Simple_repeat            1  463    (0)      1",
+        )
+        .unwrap();
+        assert_eq!(r.seqid, "chr1");
+        assert_eq!(r.pos, BedPos(10000, 10468));
+        assert_eq!(r.strand, Strand::Forward);
+        assert_eq!(r.repeat, "(CCCTAA)n");
+        let r = parse_rmsk_line(
+            " 1892  17.0  3.4  2.2  chr1        20000   20500 (248945954) C  L1PA2          LINE/L1        (100)   6000   5500      2 *",
+        )
+        .unwrap();
+        assert_eq!(r.strand, Strand::Reverse);
+        assert_eq!(r.class_family, "LINE/L1");
+        assert!(r.is_in_classes(&["LINE".to_string()]));
+        assert!(r.is_in_classes(&["SINE".to_string(), "LINE/L1".to_string()]));
+        assert!(!r.is_in_classes(&["LINE/L2".to_string()]));
+        assert!(!r.is_in_classes(&["LIN".to_string()]));
+        assert!(
+            parse_rmsk_line("  463   1.3  0.6  1.7  chr1  10468   10001 (0) +  A  B").is_none()
+        );
+    }
+
+    #[test]
+    fn test_is_rmsk_path() {
+        assert!(is_rmsk_path("hg38.fa.out"));
+        assert!(is_rmsk_path("hg38.fa.out.gz"));
+        assert!(!is_rmsk_path("hg38.bed"));
+    }
+}
@@ -1,23 +1,48 @@
-use crate::bed::{self, BedMap, BedPos};
+use crate::alias::NameAlias;
+use crate::bed::{self, BedMap, BedNameCheck, BedPos, RegionOptions};
 use crate::dna;
-use crate::io_utils::{FaReader, FqReader, FxWriter};
+use crate::io_utils::{read_name_set, FaReader, FqReader, FxWriter};
 use crate::record::RecordType;
 use crate::sub_cli::SeqArgs;
+use regex::Regex;
+use std::collections::HashSet;
 
 struct FilterParas {
     mini_seq_length: usize,
     drop_ambigous_seq: bool,
     output_odd_reads: bool,
     output_even_reads: bool,
+    include_ids: Option<HashSet<String>>,
+    exclude_ids: Option<HashSet<String>>,
+    header_regex: Option<Regex>,
+    invert_header_regex: bool,
 }
 impl FilterParas {
-    fn from(seq: &SeqArgs) -> Self {
-        FilterParas {
+    fn from(seq: &SeqArgs) -> Result<Self, std::io::Error> {
+        let include_ids = match &seq.include_ids {
+            Some(path) => Some(read_name_set(path)?),
+            None => None,
+        };
+        let exclude_ids = match &seq.exclude_ids {
+            Some(path) => Some(read_name_set(path)?),
+            None => None,
+        };
+        let header_regex = match &seq.header_regex {
+            Some(re) => Some(Regex::new(re).map_err(|e| {
+                std::io::Error::new(std::io::ErrorKind::InvalidInput, e.to_string())
+            })?),
+            None => None,
+        };
+        Ok(FilterParas {
             mini_seq_length: seq.mini_seq_length.unwrap_or(0),
             drop_ambigous_seq: seq.drop_ambigous_seq,
             output_odd_reads: seq.output_even,
             output_even_reads: seq.output_odd,
-        }
+            include_ids,
+            exclude_ids,
+            header_regex,
+            invert_header_regex: seq.invert_header_regex,
+        })
     }
 }
 struct MaskParas {
@@ -28,6 +53,12 @@ struct MaskParas {
     q_high: u8,
     mask_regions: Option<String>,
     mask_complement_region: bool,
+    mask_blocks: bool,
+    strict_bed: bool,
+    name_alias: Option<String>,
+    chr_alias: bool,
+    repeat_classes: Vec<String>,
+    mask_padding: usize,
 }
 impl MaskParas {
     fn from(seq: &SeqArgs) -> Self {
@@ -40,6 +71,12 @@ impl MaskParas {
             q_high: (seq.q_high.unwrap_or(255 - ascii_bases) + ascii_bases),
             mask_regions: seq.mask_regions.clone(),
             mask_complement_region: seq.mask_complement_region,
+            mask_blocks: seq.split,
+            strict_bed: seq.strict_bed,
+            name_alias: seq.name_alias.clone(),
+            chr_alias: seq.chr_alias,
+            repeat_classes: seq.repeat_class.clone(),
+            mask_padding: seq.mask_padding.unwrap_or(0),
         }
     }
 }
@@ -89,18 +126,19 @@ impl OutArgs {
 ///
 /// Return an error if the operation cannot be completed.
 pub fn parse_fasta(path: &str, seq: &SeqArgs) -> Result<(), std::io::Error> {
-    let fparas = FilterParas::from(seq);
+    let fparas = FilterParas::from(seq)?;
     let mparas = MaskParas::from(seq);
     let oparas = OutArgs::from(seq);
-    let bed_map = match &mparas.mask_regions {
-        Some(bed_path) => BedMap::from(bed_path)?,
-        None => BedMap::new(),
-    };
+    let bed_map = load_bed_map(&mparas)?;
+    let mut name_check = bed_name_check(&mparas);
     let fa_iter = FaReader::new(path)?;
     let mut fx_writer = FxWriter::new(oparas.output_fasta);
     for (i, record) in fa_iter.records().enumerate() {
         match record {
             Ok(read) => {
+                if let Some(check) = name_check.as_mut() {
+                    check.check(read.id(), bed_map.max_end(read.id()), read.seq().len())?;
+                }
                 let is_pass = is_pass(i + 1, &read, &fparas);
                 if is_pass {
                     modify_and_print_read(&mut fx_writer, &read, &mparas, &oparas, &bed_map, true)?;
@@ -109,6 +147,9 @@ pub fn parse_fasta(path: &str, seq: &SeqArgs) -> Result<(), std::io::Error> {
             Err(e) => eprintln!("Error read fASTA: {}", e),
         }
     }
+    if let Some(check) = &name_check {
+        check.finish(bed_map.iter().map(|(name, _)| name))?;
+    }
     Ok(())
 }
 /// Parses FASTQ file and transforms the sequences according to the arguments.
@@ -122,19 +163,19 @@ pub fn parse_fasta(path: &str, seq: &SeqArgs) -> Result<(), std::io::Error> {
 ///
 /// Return an error if the operation cannot be completed.
 pub fn parse_fastq(path: &str, seq: &SeqArgs) -> Result<(), std::io::Error> {
-    let fparas = FilterParas::from(seq);
+    let fparas = FilterParas::from(seq)?;
     let mparas = MaskParas::from(seq);
     let oparas = OutArgs::from(seq);
-    let bed_map = match &mparas.mask_regions {
-        Some(bed_path) => BedMap::from(bed_path)?,
-        None => BedMap::new(),
-    };
-
+    let bed_map = load_bed_map(&mparas)?;
+    let mut name_check = bed_name_check(&mparas);
     let fq_iter = FqReader::new(path)?;
     let mut fx_writer = FxWriter::new(oparas.output_fasta);
     for (i, record) in fq_iter.records().enumerate() {
         match record {
             Ok(read) => {
+                if let Some(check) = name_check.as_mut() {
+                    check.check(read.id(), bed_map.max_end(read.id()), read.seq().len())?;
+                }
                 let is_pass = is_pass(i + 1, &read, &fparas);
                 if is_pass {
                     modify_and_print_read(
@@ -150,9 +191,30 @@ pub fn parse_fastq(path: &str, seq: &SeqArgs) -> Result<(), std::io::Error> {
             Err(e) => eprintln!("Error read fASTQ: {}", e),
         }
     }
-
+    if let Some(check) = &name_check {
+        check.finish(bed_map.iter().map(|(name, _)| name))?;
+    }
     Ok(())
 }
+fn load_bed_map(mparas: &MaskParas) -> Result<BedMap, std::io::Error> {
+    let Some(bed_path) = &mparas.mask_regions else {
+        return Ok(BedMap::new());
+    };
+    let options = RegionOptions {
+        blocks: mparas.mask_blocks,
+        repeat_classes: mparas.repeat_classes.clone(),
+        padding: mparas.mask_padding,
+    };
+    let bed_map = BedMap::from_with(bed_path, &options)?;
+    let alias = NameAlias::from(mparas.name_alias.as_deref(), mparas.chr_alias)?;
+    Ok(bed_map.with_alias(alias))
+}
+fn bed_name_check(mparas: &MaskParas) -> Option<BedNameCheck> {
+    mparas
+        .mask_regions
+        .as_ref()
+        .map(|bed_path| BedNameCheck::new(bed_path, mparas.strict_bed))
+}
 fn modify_and_print_read(
     fx_writer: &mut FxWriter,
     read: &dyn RecordType,
@@ -301,6 +363,26 @@ fn is_pass(i: usize, read: &dyn RecordType, fparas: &FilterParas) -> bool {
     if read.seq().len().le(&fparas.mini_seq_length) {
         return false;
     }
+    if let Some(ids) = &fparas.include_ids {
+        if !ids.contains(read.id()) {
+            return false;
+        }
+    }
+    if let Some(ids) = &fparas.exclude_ids {
+        if ids.contains(read.id()) {
+            return false;
+        }
+    }
+    if let Some(re) = &fparas.header_regex {
+        // match against the whole header line (without '>' or '@')
+        let is_match = match read.desc() {
+            Some(desc) => re.is_match(&format!("{} {}", read.id(), desc)),
+            None => re.is_match(read.id()),
+        };
+        if is_match == fparas.invert_header_regex {
+            return false;
+        }
+    }
     true
 }
 
@@ -352,6 +434,12 @@ mod tests {
                 q_high: 255,
                 mask_regions: None,
                 mask_complement_region: false,
+                mask_blocks: false,
+                strict_bed: false,
+                name_alias: None,
+                chr_alias: false,
+                repeat_classes: Vec::new(),
+                mask_padding: 0,
             }
         }
         let record = Record::with_attrs("SEQ_ID_1", None, b"ATCGATcgACTTG", b"!(*AAAABbbaaz");
@@ -426,6 +514,10 @@ mod tests {
                 drop_ambigous_seq: false,
                 output_odd_reads: false,
                 output_even_reads: false,
+                include_ids: None,
+                exclude_ids: None,
+                header_regex: None,
+                invert_header_regex: false,
             }
         }
         let mut fparas = init_fparas();
@@ -454,6 +546,28 @@ mod tests {
         fparas.output_even_reads = true;
         assert_eq!(is_pass(3, &record, &fparas), true);
         assert_eq!(is_pass(4, &record, &fparas), false);
+
+        // [05] include / exclude ids
+        fparas = init_fparas();
+        fparas.include_ids = Some(HashSet::from(["@SEQ_ID_2".to_string()]));
+        assert!(!is_pass(0, &record, &fparas));
+        assert!(is_pass(0, &record2, &fparas));
+        fparas = init_fparas();
+        fparas.exclude_ids = Some(HashSet::from(["@SEQ_ID_2".to_string()]));
+        assert!(is_pass(0, &record, &fparas));
+        assert!(!is_pass(0, &record2, &fparas));
+
+        // [06] header regex (matched against id + desc)
+        fparas = init_fparas();
+        let record3 = Record::with_attrs("read_3", Some("phiX174"), b"ACGT", b"!!<A");
+        fparas.header_regex = Some(Regex::new("_1$|phiX").unwrap());
+        assert!(is_pass(0, &record, &fparas));
+        assert!(!is_pass(0, &record2, &fparas));
+        assert!(is_pass(0, &record3, &fparas));
+        fparas.invert_header_regex = true;
+        assert!(!is_pass(0, &record, &fparas));
+        assert!(is_pass(0, &record2, &fparas));
+        assert!(!is_pass(0, &record3, &fparas));
     }
     #[test]
     fn test_add_newlines() {
@@ -1,11 +1,14 @@
 use std::collections::HashMap;
 
+/// The highest quality score of [`Q2PConverter`]
+pub const MAX_Q2P_SCORE: u8 = 179;
+
 #[derive(Debug)]
 pub struct Q2PConverter(HashMap<u8, f64>);
 impl Q2PConverter {
     pub fn new(asciibase: u8) -> Self {
-        let table = (0..180)
-            .map(|i| (i as u8 + asciibase, convert_q_score_to_p_err(i as f64)))
+        let table = (0..=MAX_Q2P_SCORE)
+            .map(|i| (i + asciibase, convert_q_score_to_p_err(i as f64)))
             .collect::<HashMap<_, _>>();
         Q2PConverter(table)
     }
@@ -1,4 +1,4 @@
-use clap::{ArgGroup, Args, Parser, Subcommand};
+use clap::{ArgGroup, Args, Parser, Subcommand, ValueEnum};
 use colored::*;
 
 #[derive(Parser)]
@@ -17,6 +17,129 @@ pub enum Commands {
     #[command(about = "Random Sampling by given seed and fraction")]
     Sample(SampleArgs),
 
+    #[command(
+        about = "Keep the best reads by length and mean quality up to a base budget",
+        long_about = "\x1b[1mSelect\n\x1b[0m\
+                    Keep the highest-scoring reads whose total length fits --target-bases (similar to Filtlong). The output preserves the input order.\n\n\
+                    \x1b[1;4mThe score:\x1b[0m\n\
+                    \tscore = w_len * ln(length) + w_qual * ln(meanQ)\n\
+                    where meanQ = -10 * log₁₀((P₁ + P₂ + ... + Pₙ) / N) is the Phred-scaled mean error probability of the read.\n\
+                    FASTA records have no quality, so they are scored by length only."
+    )]
+    Select(SelectArgs),
+
+    #[command(
+        about = "Extract sequences or regions by a name list or a BED file",
+        long_about = "\x1b[1mSubseq\n\x1b[0m\
+                    Extract sequences by a name list or regions by a BED (0-based) file from FASTA/Q.\n\n\
+                    \x1b[1;4mOutput:\x1b[0m\n\
+                    (1) --name-list: Whole records whose names are in the list (the first column of each line).\n\
+                    (2) --in-bed: One record per interval named after the BED name column, or `chrom:start-end` if there is no name, \
+                    where start-end is 1-based and inclusive (e.g., `chr1 99 200` is named `chr1:100-200`). \
+                    Intervals on the minus strand (BED6) are reverse-complemented, and `(-)` is appended to unnamed ones. \
+                    The quality of FASTQ records is sliced (and reversed) together with the sequence.\n\
+                    (3) --in-bed --split: Same as (2), but the blocks of each BED12 feature are joined into one spliced sequence."
+    )]
+    Subseq(SubseqArgs),
+
+    #[command(
+        about = "Extract features of a GFF3/GTF file by the feature type (e.g., CDS, exon, gene)",
+        long_about = "\x1b[1mGetfeat\n\x1b[0m\
+                    Extract features of a GFF3/GTF (1-based, closed) file by the feature type (e.g., CDS, exon, gene) from FASTA/Q.\n\n\
+                    \x1b[1;4mOutput:\x1b[0m\n\
+                    (1) Features are grouped by the parent (Parent of GFF3 or transcript_id of GTF), so that a multi-part CDS is joined into one sequence.\n\
+                    (2) Features on the minus strand are reverse-complemented.\n\
+                    (3) Records are named by --name-attr, or by the group ID if the attribute is absent. The group ID is added as the comment."
+    )]
+    Getfeat(GetfeatArgs),
+
+    #[command(
+        about = "Search motifs with IUPAC codes and mismatches on both strands (Output: BED6)",
+        long_about = "\x1b[1mLocate\n\x1b[0m\
+                    Search motifs (e.g., primers, restriction sites or barcodes) on both strands of FASTA/Q.\n\n\
+                    \x1b[1;4mOutput columns (BED6):\x1b[0m\n\
+                    (1) chrom: Sequence name\n\
+                    (2-3) start, end: Position of the hit on the forward strand (0-based, half-open)\n\
+                    (4) name: Motif name (the motif itself for --motif, or the record name for --motif-fa)\n\
+                    (5) score: Number of mismatches\n\
+                    (6) strand: + or -\n\n\
+                    \x1b[1;4mNote:\x1b[0m\n\
+                    Motifs may contain IUPAC degenerate codes. A base of the sequence matches a motif base if every nucleotide it stands for is allowed by the motif base \
+                    (e.g., A matches R, but N matches only N). \
+                    The reverse strand is not searched for a motif equal to its own reverse complement (e.g., GAATTC)."
+    )]
+    Locate(LocateArgs),
+
+    #[command(
+        about = "Report soft-masked (lowercase), N-gap or other character-class runs as BED",
+        long_about = "\x1b[1mMaskbed\n\x1b[0m\
+                    Scan FASTA/Q and report the runs of the chosen character classes as BED3 (0-based, half-open), \
+                    e.g., where an assembly is soft-masked (-c lower) or where its N gaps are (-c n -l 10).\n\n\
+                    \x1b[1;4mClasses (the same as comp):\x1b[0m\n\
+                    (1) lower: Lowercase (soft-masked) IUPAC codes\n\
+                    (2) n: N or n\n\
+                    (3) iupac2: R, Y, S, W, K, M (either case)\n\
+                    (4) iupac3: B, D, H, V (either case)\n\
+                    (5) other: Characters other than IUPAC codes\n\
+                    A run consists of the bases in any of the chosen classes."
+    )]
+    Maskbed(MaskbedArgs),
+
+    #[command(
+        about = "Split sequences into contigs at N gaps",
+        long_about = "\x1b[1mCutn\n\x1b[0m\
+                    Break the scaffolds of FASTA/Q into contigs at the runs of N (or n) of at least --min-gap bases. \
+                    Shorter runs are kept in the contigs.\n\n\
+                    \x1b[1;4mOutput:\x1b[0m\n\
+                    (1) The contigs to stdout, named `name:start-end` by their positions in the scaffold (1-based, inclusive).\n\
+                    (2) --gap-bed: The removed gaps as BED3 (0-based, half-open), including the gaps at both ends.\n\
+                    (3) --agp: AGP 2.1 describing the scaffolds by the contigs (W) and the gaps between them (N, scaffold, yes, unspecified). \
+                    The gaps at both ends are not part of the objects, so an object starts at the first base of its first contig."
+    )]
+    Cutn(CutnArgs),
+
+    #[command(
+        about = "Rename sequences by an alias table or the chromosome naming style (chr1 <-> 1)",
+        long_about = "\x1b[1mRename\n\x1b[0m\
+                    Rename the records of FASTA/Q by the same mapping as BED lookups use (--name-alias and --chr-alias of seq, comp and subseq).\n\n\
+                    \x1b[1;4mMapping:\x1b[0m\n\
+                    (1) --name-alias: Two-column file (name alias). The 1st column is renamed to the 2nd column.\n\
+                    (2) --to-ucsc: 1-22, X and Y to chr1-chr22, chrX and chrY, MT to chrM (other names are kept). --to-ensembl: chr1 to 1, chrM to MT. Applied after (1).\n\
+                    Records not matched keep their names. The comments, sequences and qualities are kept as they are."
+    )]
+    Rename(RenameArgs),
+
+    #[command(
+        about = "Interval operations on a BED file (merge, intersect, subtract, complement, slop, flank)",
+        long_about = "\x1b[1mBed\n\x1b[0m\
+                    Interval operations on a BED (0-based, half-open), GFF3/GTF or RepeatMasker .out file. \
+                    RepeatMasker repeats can be selected by --repeat-class (e.g., `bed merge genome.fa.out --repeat-class LINE/L1` converts them to BED).\n\n\
+                    \x1b[1;4mOperations:\x1b[0m\n\
+                    (1) merge: Merge overlapping and book-ended intervals.\n\
+                    (2) intersect: Regions covered by both <in_bed> and --other-bed.\n\
+                    (3) subtract: Regions of <in_bed> not covered by --other-bed.\n\
+                    (4) complement: Regions of the sequences in --genome not covered by <in_bed>.\n\
+                    (5) slop: Extend each feature by --left and --right bases.\n\
+                    (6) flank: The --left and --right bases next to each feature (the feature itself is excluded).\n\n\
+                    \x1b[1;4mOutput:\x1b[0m\n\
+                    (1-4) BED3 of the merged intervals, sorted by the sequence name (or the order of --genome) and position.\n\
+                    (5-6) Each feature with the name, score and strand columns (BED6) if present. \
+                    With --genome, the results are clipped to the sequence lengths. \
+                    With --strand, --left and --right are upstream and downstream of the features on the minus strand."
+    )]
+    Bed(BedArgs),
+
+    #[command(
+        about = "Build a FASTA index (.fai) or extract regions by the index",
+        long_about = "\x1b[1mFaidx\n\x1b[0m\
+                    Build a samtools-compatible index <in_fa>.fai if no region is given. \
+                    Otherwise output the regions by seeking into the FASTA (the index is built first if it does not exist).\n\
+                    A BGZF-compressed FASTA (by `bgzip`) is supported with a block index <in_fa>.gzi as well.\n\n\
+                    \x1b[1;4mRegion:\x1b[0m\n\
+                    `name`, `name:start` or `name:start-end` (1-based, inclusive)"
+    )]
+    Faidx(FaidxArgs),
+
     #[command(
         about = "Report the stats of sequence length (Output: #seq, #bases, avg_size, min_size, med_size, max_size, N50)"
     )]
@@ -69,10 +192,141 @@ pub enum Commands {
                     (3) Find out the maximun score. This is the end of the trimmed read.\n\
                     (4) If all base quality are less than Q, the read is discarded.\n\
                     (5) If the trimmed read is shorter than min_len, we first extend it from the 3’ end. If still too short, extend form the 5’ end until min_len is reached or no more bases are available.\
+                    \n\n\x1b[1;4mOrder of trimming:\n\x1b[0m\
+                    (1) --trim-left and --trim-right remove fixed numbers of bases from both ends of the raw read.\n\
+                    (2) Quality trimming (above) on the rest of the read. The extension to min_len does not go beyond (1). Use -q 0 to skip it.\n\
+                    (3) --max-length keeps at most the first bases of the result, or --truncate-to cuts it to exactly the length (shorter reads are discarded).\n\
+                    The qualities are always cut at the same positions as the bases.\
+                    \n\n\x1b[1;4mMethods (--method):\n\x1b[0m\
+                    (1) sum: The algorithm above.\n\
+                    (2) window: Remove the bases below --leading from the 5’ end and below --trailing from the 3’ end, \
+                    then cut the read at the start of the first window of --window-size bases with the mean quality below Q (Trimmomatic LEADING, TRAILING and SLIDINGWINDOW).\n\
+                    (3) max-ee: Cut the read where the cumulative expected errors (the sum of the error probabilities from the 5’ end) exceed --max-ee.\n\
+                    With --discard-short, reads shorter than min_len are discarded instead of extended (4-5 above apply to all methods otherwise).\
+                    \n\n\x1b[1;4mHomopolymer tails (--poly-x):\n\x1b[0m\
+                    High-quality runs like the poly-G tails of two-colour chemistry pass quality trimming. \
+                    With --poly-x, the 3’ tail of the base (or of any base for x) is removed after the fixed-position trimming and before quality trimming. \
+                    A tail needs at least --poly-min-len bases, starts with the base, and has at most floor(length * --poly-error-rate) mismatches.\
                     \n\n\x1b[1;4mNotes:\n\x1b[0m\
                     Quality trimming is no longer necessary in most modern sequencing pipelines. Its usefulness depends on the sequencing technology and the goals of your downstream analysis."
     )]
     Qctrim(QCTrimArgs),
+
+    #[command(
+        about = "Trim 3' adapters from FASTA/Q by built-in (TruSeq, Nextera, small RNA) or custom adapters",
+        long_about = "\x1b[1mTrimadapter\n\x1b[0m\
+                    Remove the 3' adapter and everything after it from each read.\n\n\
+                    \x1b[1;4mMatching:\x1b[0m\n\
+                    (1) An adapter may start anywhere in the read. At the 3' end, a prefix of the adapter (a partial overlap) is matched as well.\n\
+                    (2) A match needs at least --min-overlap bases and at most floor(overlap * --error-rate) mismatches (no indels). \
+                    N in an adapter matches any base, while N in a read matches only N.\n\
+                    (3) The leftmost match of all adapters is trimmed.\n\n\
+                    \x1b[1;4mAdapters:\x1b[0m\n\
+                    (1) truseq: AGATCGGAAGAGCACACGTCTGAACTCCAGTCA (read 1), AGATCGGAAGAGCGTCGTGTAGGGAAAGAGTGT (read 2)\n\
+                    (2) nextera: CTGTCTCTTATACACATCT\n\
+                    (3) small-rna: TGGAATTCTCGGGTGCCAAGG\n\
+                    (4) --adapter-fa: FASTA of custom adapters (named by the record names)\n\n\
+                    \x1b[1;4mPaired-end mode (--in-fq2):\x1b[0m\n\
+                    (1) Read 1 is aligned with the reverse complement of read 2 to find the insert length. \
+                    An alignment needs at least --min-insert-overlap bases and at most floor(overlap * --error-rate) mismatches. \
+                    If the insert is shorter than a mate, both mates are cut at the insert boundary, which finds adapters too short to match.\n\
+                    (2) Otherwise, the mates are trimmed by the adapters (if any) one by one.\n\
+                    (3) The mates are kept in sync: a pair is discarded if either mate is shorter than --min-length. \
+                    The pairs are output interleaved, or to --out1 and --out2.\n\n\
+                    \x1b[1;4mHomopolymer tails (--poly-x):\x1b[0m\n\
+                    After the adapters (or the insert boundary), the 3’ tail of the base (or of any base for x) is removed from each read (each mate), \
+                    as qctrim --poly-x does.\n\n\
+                    \x1b[1;4mReport (stderr):\x1b[0m\n\
+                    The number of reads and bases trimmed per adapter."
+    )]
+    Trimadapter(TrimAdapterArgs),
+
+    #[command(
+        about = "Detect the 3' adapter from overrepresented k-mers at the read ends",
+        long_about = "\x1b[1mDetectadapter\n\x1b[0m\
+                    Find the adapter of a library with unknown preparation and output it as FASTA, \
+                    which can be given to `trimadapter --adapter-fa` as it is.\n\n\
+                    \x1b[1;4mThe algorithm:\x1b[0m\n\
+                    (1) Count the k-mers in the last --tail bases of the first --sample-reads reads.\n\
+                    (2) Take the most frequent k-mer as the seed, skipping low-complexity k-mers (fewer than 3 distinct bases). \
+                    The seed must occur in at least --min-ratio of the reads.\n\
+                    (3) Extend the seed base by base to both sides while one base is dominant (>= 80% of the counts of the 4 possible k-mers) \
+                    and its k-mer is still frequent enough. A random insert sequence is not dominated by one base, so the extension to the left stops at the start of the adapter.\n\
+                    (4) With --match-builtin, the built-in adapter sharing a k-mer prefix with the result (see trimadapter) is output instead, \
+                    with the assembled sequence in the description."
+    )]
+    Detectadapter(DetectAdapterArgs),
+
+    #[command(
+        about = "Trim degenerate PCR primers from the 5' end and orient amplicon reads",
+        long_about = "\x1b[1mTrimprimer\n\x1b[0m\
+                    Remove the PCR primers of amplicon reads and flip the reads to the forward orientation.\n\n\
+                    \x1b[1;4mPrimers (--primer-fa):\x1b[0m\n\
+                    Pairs of consecutive records (the forward primer, then the reverse primer), named by the forward primer. \
+                    IUPAC codes are allowed, and a base of the read matches a primer base if every nucleotide it stands for is allowed (as locate).\n\n\
+                    \x1b[1;4mThe algorithm:\x1b[0m\n\
+                    (1) Match the forward and the reverse primers of all pairs at the 5' end of the read with at most --max-mismatches. \
+                    The match with the fewest mismatches wins (the first pair and the forward primer on a tie).\n\
+                    (2) Cut the primer, and the reverse complement of the other primer of the pair if it occurs later in the read (the amplicon end).\n\
+                    (3) Reverse-complement the reads starting with the reverse primer (the qualities are reversed).\n\
+                    (4) Append `primer=<pair> strand=<+|->` to the header (strand - for the flipped reads).\n\
+                    Reads without a primer are output as they are, or discarded with --discard-untrimmed. \
+                    The numbers of reads per primer pair and orientation are reported to stderr."
+    )]
+    Trimprimer(TrimPrimerArgs),
+
+    #[command(
+        about = "Extract UMIs from the reads into the read names",
+        long_about = "\x1b[1mUmi\n\x1b[0m\
+                    Move the UMI (unique molecular identifier) bases from the 5' end of the reads to the read names, \
+                    where deduplication tools (e.g., UMI-tools) expect them: `@read1` becomes `@read1_ACGTACGT`.\n\n\
+                    \x1b[1;4mPattern (--pattern):\x1b[0m\n\
+                    N: a UMI base, X: a base to skip (e.g., a linker). Both are removed from the read with their qualities, \
+                    and the rest of the read is kept. E.g., NNNNNNNNXXX takes an 8-base UMI and removes 3 more bases.\n\n\
+                    \x1b[1;4mSources of the UMI:\x1b[0m\n\
+                    (1) Read 1 (-I) by --pattern, and read 2 (-2) by --pattern2 if given. The UMI of a pair is that of read 1 followed by that of read 2.\n\
+                    (2) --umi-fq: An index read (e.g., I1) by --pattern instead. Reads 1 and 2 are kept as they are.\n\
+                    Both mates get the same UMI, and pairs are output interleaved or to --out1 and --out2. \
+                    Reads (pairs) shorter than a pattern are discarded. With --umi-qual, the UMI qualities are added to the description as UQ:Z:<qual>."
+    )]
+    Umi(UmiArgs),
+
+    #[command(
+        about = "Demultiplex reads into per-sample FASTQ files by barcodes",
+        long_about = "\x1b[1mDemux\n\x1b[0m\
+                    Assign the reads (pairs) to the samples by their barcodes and write a FASTQ per sample.\n\n\
+                    \x1b[1;4mSample sheet (--sample-sheet):\x1b[0m\n\
+                    One sample per line: `sample barcode1 [barcode2]` (separated by whitespace) or `sample barcode1+barcode2`. \
+                    Lines starting with # are ignored. Sample names may contain only A-Z, a-z, 0-9, '.', '_' and '-' (without '..'). \
+                    All samples must have the same number (1 or 2) and lengths of A/C/G/T barcodes.\n\n\
+                    \x1b[1;4mBarcode locations (--location):\x1b[0m\n\
+                    header: The last field of the read 1 description, as Illumina `1:N:0:ACGTACGT+TTGGCCAA` (the barcodes are compared as prefixes).\n\
+                    inline: Barcode 1 at the 5' end of read 1, and barcode 2 at the 5' end of read 2. The barcodes are removed from the assigned reads.\n\n\
+                    \x1b[1;4mAssignment:\x1b[0m\n\
+                    A read (pair) matches a sample if every barcode has at most --max-mismatches (Hamming distance, N is a mismatch), \
+                    and is assigned to the matching sample with the fewest mismatches in total. \
+                    Reads matching several samples equally well go to `ambiguous`, and those matching none to `undetermined`.\n\n\
+                    \x1b[1;4mOutputs (--out-dir):\x1b[0m\n\
+                    <sample>.fastq, or <sample>_R1.fastq and <sample>_R2.fastq for pairs (kept in sync), including undetermined and ambiguous. \
+                    The numbers of reads (pairs) and of those with mismatches per sample are reported to stderr."
+    )]
+    Demux(DemuxArgs),
+
+    #[command(
+        about = "Collapse reads sharing a UMI and start sequence into consensus reads",
+        long_about = "\x1b[1mDedup\n\x1b[0m\
+                    Collapse the PCR duplicates of UMI-tagged reads (e.g., by umi, the UMI after the last --separator of the read name) into consensus reads.\n\n\
+                    \x1b[1;4mThe algorithm:\x1b[0m\n\
+                    (1) Group the reads by their first --start-len bases (0: by the UMIs only).\n\
+                    (2) Cluster the UMIs of a group by the directional adjacency of UMI-tools: from the most abundant UMI, \
+                    UMI b joins the family of UMI a if they differ by at most --distance bases and count(a) >= 2 * count(b) - 1.\n\
+                    (3) Build a per-position majority consensus of each family (N does not vote, ties are broken by the sum of the qualities), \
+                    as long as the longest length covered by at least half of the reads. \
+                    The quality is the sum of the agreeing qualities minus that of the disagreeing ones, capped at 2..=41 (Phred+33).\n\
+                    (4) Output the consensus named as the first read of the family, with `family_size=<reads>` in the description.\n\
+                    Families smaller than --min-size are dropped. The family size distribution is reported to stderr."
+    )]
+    Dedup(DedupArgs),
 }
 
 #[derive(Args)]
@@ -87,6 +341,194 @@ pub struct FqchkArgs {
     pub ascii_base: Option<u8>,
 }
 
+#[derive(Args)]
+#[command(group(
+    ArgGroup::new("exclusive_group")
+        .args(["in_fq", "in_fa"])
+        .required(true)
+        .multiple(false)
+))]
+#[command(group(
+    ArgGroup::new("motif_group")
+        .args(["motif", "motif_fa"])
+        .required(true)
+        .multiple(true)
+))]
+pub struct LocateArgs {
+    #[arg(short = 'I', long)]
+    /// FASTQ path
+    pub in_fq: Option<String>,
+    #[arg(short = 'A', long)]
+    /// FASTA path
+    pub in_fa: Option<String>,
+    #[arg(short = 'm', long)]
+    /// Motif to search (IUPAC codes are allowed). Can be specified multiple times
+    pub motif: Vec<String>,
+    #[arg(short = 'f', long)]
+    /// FASTA file of motifs (named by the record names)
+    pub motif_fa: Option<String>,
+    #[arg(short = 'k', long)]
+    /// Maximum number of mismatches [default: 0]
+    pub max_mismatches: Option<usize>,
+}
+
+#[derive(Args)]
+#[command(group(
+    ArgGroup::new("exclusive_group")
+        .args(["in_fq", "in_fa"])
+        .required(true)
+        .multiple(false)
+))]
+pub struct GetfeatArgs {
+    #[arg(short = 'I', long)]
+    /// FASTQ path
+    pub in_fq: Option<String>,
+    #[arg(short = 'A', long)]
+    /// FASTA path
+    pub in_fa: Option<String>,
+    #[arg(short = 'g', long)]
+    /// GFF3/GTF path
+    pub gff: String,
+    #[arg(short = 't', long)]
+    /// Feature type to extract (the 3rd column, e.g., CDS, exon, gene) [default: CDS]
+    pub feature_type: Option<String>,
+    #[arg(short = 'n', long)]
+    /// Attribute used to name the output records [default: gene_name]
+    pub name_attr: Option<String>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum MaskClass {
+    Lower,
+    N,
+    Iupac2,
+    Iupac3,
+    Other,
+}
+
+#[derive(Args)]
+#[command(group(
+    ArgGroup::new("exclusive_group")
+        .args(["in_fq", "in_fa"])
+        .required(true)
+        .multiple(false)
+))]
+pub struct MaskbedArgs {
+    #[arg(short = 'I', long)]
+    /// FASTQ path
+    pub in_fq: Option<String>,
+    #[arg(short = 'A', long)]
+    /// FASTA path
+    pub in_fa: Option<String>,
+    #[arg(short = 'c', long, value_enum)]
+    /// Character class to report. Can be specified multiple times [default: lower]
+    pub class: Vec<MaskClass>,
+    #[arg(short = 'l', long)]
+    /// Minimum length of a run (e.g., the minimum N gap) [default: 1]
+    pub min_len: Option<usize>,
+}
+
+#[derive(Args)]
+#[command(group(
+    ArgGroup::new("exclusive_group")
+        .args(["in_fq", "in_fa"])
+        .required(true)
+        .multiple(false)
+))]
+pub struct CutnArgs {
+    #[arg(short = 'I', long)]
+    /// FASTQ path
+    pub in_fq: Option<String>,
+    #[arg(short = 'A', long)]
+    /// FASTA path
+    pub in_fa: Option<String>,
+    #[arg(short = 'n', long, value_parser = clap::value_parser!(u64).range(1..))]
+    /// Minimum length of an N gap to split at [default: 1000]
+    pub min_gap: Option<u64>,
+    #[arg(short = 'g', long)]
+    /// Write the removed gaps to this BED file
+    pub gap_bed: Option<String>,
+    #[arg(long)]
+    /// Write the AGP of the scaffolds to this file
+    pub agp: Option<String>,
+}
+
+#[derive(Args)]
+#[command(group(
+    ArgGroup::new("exclusive_group")
+        .args(["in_fq", "in_fa"])
+        .required(true)
+        .multiple(false)
+))]
+#[command(group(
+    ArgGroup::new("mapping_group")
+        .args(["name_alias", "to_ucsc", "to_ensembl"])
+        .required(true)
+        .multiple(true)
+))]
+pub struct RenameArgs {
+    #[arg(short = 'I', long)]
+    /// FASTQ path
+    pub in_fq: Option<String>,
+    #[arg(short = 'A', long)]
+    /// FASTA path
+    pub in_fa: Option<String>,
+    #[arg(short = 'm', long)]
+    /// Two-column file of sequence name aliases (the 1st column is renamed to the 2nd column)
+    pub name_alias: Option<String>,
+    #[arg(long, conflicts_with = "to_ensembl")]
+    /// Add the `chr` prefix to the primary chromosomes (1-22, X, Y and MT to chrM)
+    pub to_ucsc: bool,
+    #[arg(long)]
+    /// Remove the `chr` prefix (e.g., chr1 to 1, chrM to MT)
+    pub to_ensembl: bool,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum BedOperation {
+    Merge,
+    Intersect,
+    Subtract,
+    Complement,
+    Slop,
+    Flank,
+}
+
+#[derive(Args)]
+pub struct BedArgs {
+    /// Operation
+    #[arg(value_enum)]
+    pub operation: BedOperation,
+    /// BED, GFF3/GTF or RepeatMasker .out path
+    pub in_bed: String,
+    #[arg(short = 'b', long)]
+    /// The other BED path (for intersect and subtract)
+    pub other_bed: Option<String>,
+    #[arg(short = 'g', long)]
+    /// FASTA or .fai path for the sequence lengths (required by complement)
+    pub genome: Option<String>,
+    #[arg(short = 'l', long)]
+    /// Number of bases on the left (for slop and flank) [default: 0]
+    pub left: Option<usize>,
+    #[arg(short = 'r', long)]
+    /// Number of bases on the right (for slop and flank) [default: 0]
+    pub right: Option<usize>,
+    #[arg(short = 's', long)]
+    /// Define --left and --right by the strand (i.e., upstream and downstream)
+    pub strand: bool,
+    #[arg(long)]
+    /// Only load the repeats of the class/family from a RepeatMasker .out file (e.g., LINE/L1, LINE, Simple_repeat). Can be specified multiple times
+    pub repeat_class: Vec<String>,
+}
+
+#[derive(Args)]
+pub struct FaidxArgs {
+    /// FASTA path
+    pub in_fa: String,
+    /// Regions to extract (name, name:start or name:start-end; 1-based) [default: build the index only]
+    pub regions: Vec<String>,
+}
+
 #[derive(Args)]
 pub struct QCTrimArgs {
     /// FASTQ path
@@ -100,6 +542,260 @@ pub struct QCTrimArgs {
     #[arg(short, long)]
     /// Ascii value [default: 33]
     pub ascii_base: Option<u8>,
+    #[arg(short = 'b', long)]
+    /// Number of bases to remove from the 5' end before quality trimming [default: 0]
+    pub trim_left: Option<usize>,
+    #[arg(short = 'e', long)]
+    /// Number of bases to remove from the 3' end before quality trimming [default: 0]
+    pub trim_right: Option<usize>,
+    #[arg(short = 'L', long, conflicts_with = "truncate_to")]
+    /// Keep at most this many bases from the 5' end after quality trimming
+    pub max_length: Option<usize>,
+    #[arg(long)]
+    /// Cut reads to exactly this length after quality trimming, discarding shorter reads
+    pub truncate_to: Option<usize>,
+    #[arg(long, value_enum)]
+    /// Quality-trimming algorithm [default: sum]
+    pub method: Option<TrimMethod>,
+    #[arg(short = 'w', long)]
+    /// Window size of --method window [default: 4]
+    pub window_size: Option<usize>,
+    #[arg(long)]
+    /// Remove the bases below this quality from the 5' end (--method window) [default: 0]
+    pub leading: Option<u8>,
+    #[arg(long)]
+    /// Remove the bases below this quality from the 3' end (--method window) [default: 0]
+    pub trailing: Option<u8>,
+    #[arg(long)]
+    /// Maximum expected errors of --method max-ee [default: 1.0]
+    pub max_ee: Option<f64>,
+    #[arg(short = 'd', long)]
+    /// Discard reads shorter than --min-length after trimming, instead of extending them
+    pub discard_short: bool,
+    #[arg(long, value_enum, ignore_case = true)]
+    /// Trim the 3' homopolymer tail of this base (x: any base), e.g., G for two-colour chemistry or A for poly(A)
+    pub poly_x: Option<PolyBase>,
+    #[arg(long, requires = "poly_x")]
+    /// Minimum length of a homopolymer tail [default: 10]
+    pub poly_min_len: Option<usize>,
+    #[arg(long, requires = "poly_x", value_parser = validate_ratio)]
+    /// Maximum mismatch rate (mismatches / tail length) of a homopolymer tail [default: 0.1]
+    pub poly_error_rate: Option<f64>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum PolyBase {
+    A,
+    C,
+    G,
+    T,
+    /// Any base
+    X,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum TrimMethod {
+    /// Running sum of (quality - Q), as seqtk trimfq
+    Sum,
+    /// Trimmomatic-style LEADING, TRAILING and SLIDINGWINDOW
+    Window,
+    /// Truncation at the cumulative expected errors of --max-ee
+    MaxEe,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum AdapterKind {
+    Truseq,
+    Nextera,
+    SmallRna,
+}
+
+#[derive(Args)]
+#[command(group(
+    ArgGroup::new("exclusive_group")
+        .args(["in_fq", "in_fa"])
+        .required(true)
+        .multiple(false)
+))]
+pub struct TrimAdapterArgs {
+    #[arg(short = 'I', long)]
+    /// FASTQ path (read 1 in the paired-end mode)
+    pub in_fq: Option<String>,
+    #[arg(short = 'A', long)]
+    /// FASTA path
+    pub in_fa: Option<String>,
+    #[arg(short = '2', long, requires = "in_fq")]
+    /// FASTQ path of read 2. Enables the paired-end mode
+    pub in_fq2: Option<String>,
+    #[arg(long, requires_all = ["in_fq2", "out2"])]
+    /// Output path of read 1 in the paired-end mode [default: interleaved to stdout]
+    pub out1: Option<String>,
+    #[arg(long, requires_all = ["in_fq2", "out1"])]
+    /// Output path of read 2 in the paired-end mode
+    pub out2: Option<String>,
+    #[arg(short = 'b', long, value_enum)]
+    /// Built-in adapter set. Can be specified multiple times
+    pub builtin: Vec<AdapterKind>,
+    #[arg(short = 'a', long)]
+    /// FASTA file of custom adapters
+    pub adapter_fa: Option<String>,
+    #[arg(short = 'e', long, value_parser = validate_ratio)]
+    /// Maximum error rate (mismatches / overlap) of a match [default: 0.1]
+    pub error_rate: Option<f64>,
+    #[arg(short = 'O', long)]
+    /// Minimum overlap between a read and an adapter [default: 3]
+    pub min_overlap: Option<usize>,
+    #[arg(short = 'm', long)]
+    /// Discard reads (pairs if either mate) shorter than this after trimming [default: 0]
+    pub min_length: Option<usize>,
+    #[arg(long)]
+    /// Minimum overlap of the mates to detect the insert in the paired-end mode [default: 20]
+    pub min_insert_overlap: Option<usize>,
+    #[arg(long, value_enum, ignore_case = true)]
+    /// Trim the 3' homopolymer tail of this base (x: any base), e.g., G for two-colour chemistry or A for poly(A)
+    pub poly_x: Option<PolyBase>,
+    #[arg(long, requires = "poly_x")]
+    /// Minimum length of a homopolymer tail [default: 10]
+    pub poly_min_len: Option<usize>,
+    #[arg(long, requires = "poly_x", value_parser = validate_ratio)]
+    /// Maximum mismatch rate (mismatches / tail length) of a homopolymer tail [default: 0.1]
+    pub poly_error_rate: Option<f64>,
+}
+
+#[derive(Args)]
+pub struct UmiArgs {
+    #[arg(short = 'I', long)]
+    /// FASTQ path (read 1)
+    pub in_fq: String,
+    #[arg(short = '2', long)]
+    /// FASTQ path of read 2
+    pub in_fq2: Option<String>,
+    #[arg(short = 'u', long, conflicts_with = "pattern2")]
+    /// FASTQ path of the index read with the UMI
+    pub umi_fq: Option<String>,
+    #[arg(short = 'p', long)]
+    /// UMI pattern of read 1 (or of the index read): N for a UMI base, X for a base to skip
+    pub pattern: String,
+    #[arg(long, requires = "in_fq2")]
+    /// UMI pattern of read 2
+    pub pattern2: Option<String>,
+    #[arg(short = 's', long)]
+    /// Separator between the read name and the UMI [default: _]
+    pub separator: Option<String>,
+    #[arg(long)]
+    /// Add the UMI qualities to the description (UQ:Z:<qual>)
+    pub umi_qual: bool,
+    #[arg(long, requires_all = ["in_fq2", "out2"])]
+    /// Output path of read 1 for pairs [default: interleaved to stdout]
+    pub out1: Option<String>,
+    #[arg(long, requires_all = ["in_fq2", "out1"])]
+    /// Output path of read 2 for pairs
+    pub out2: Option<String>,
+}
+
+#[derive(Args)]
+pub struct DemuxArgs {
+    #[arg(short = 'I', long)]
+    /// FASTQ path (read 1)
+    pub in_fq: String,
+    #[arg(short = '2', long)]
+    /// FASTQ path of read 2
+    pub in_fq2: Option<String>,
+    #[arg(short = 's', long)]
+    /// Sample sheet of the samples and their barcodes
+    pub sample_sheet: String,
+    #[arg(short = 'l', long, value_enum)]
+    /// Location of the barcodes [default: header]
+    pub location: Option<BarcodeLocation>,
+    #[arg(short = 'k', long)]
+    /// Maximum number of mismatches of a barcode [default: 1]
+    pub max_mismatches: Option<usize>,
+    #[arg(short = 'o', long)]
+    /// Output directory
+    pub out_dir: String,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum BarcodeLocation {
+    /// The index field of the read 1 header
+    Header,
+    /// The 5' end of the reads
+    Inline,
+}
+
+#[derive(Args)]
+pub struct DedupArgs {
+    #[arg(short = 'I', long)]
+    /// FASTQ path with UMIs in the read names
+    pub in_fq: String,
+    #[arg(short = 's', long)]
+    /// Separator between the read name and the UMI [default: _]
+    pub separator: Option<String>,
+    #[arg(short = 'd', long)]
+    /// Maximum number of mismatches between the UMIs of a family [default: 1]
+    pub distance: Option<usize>,
+    #[arg(short = 'l', long)]
+    /// Length of the start sequence shared by a family [default: 10]
+    pub start_len: Option<usize>,
+    #[arg(short = 'm', long)]
+    /// Minimum family size to output [default: 1]
+    pub min_size: Option<usize>,
+}
+
+#[derive(Args)]
+#[command(group(
+    ArgGroup::new("exclusive_group")
+        .args(["in_fq", "in_fa"])
+        .required(true)
+        .multiple(false)
+))]
+pub struct TrimPrimerArgs {
+    #[arg(short = 'I', long)]
+    /// FASTQ path
+    pub in_fq: Option<String>,
+    #[arg(short = 'A', long)]
+    /// FASTA path
+    pub in_fa: Option<String>,
+    #[arg(short = 'p', long)]
+    /// FASTA file of primer pairs (forward, then reverse)
+    pub primer_fa: String,
+    #[arg(short = 'k', long)]
+    /// Maximum number of mismatches of a primer [default: 2]
+    pub max_mismatches: Option<usize>,
+    #[arg(short = 'd', long)]
+    /// Discard reads without a primer
+    pub discard_untrimmed: bool,
+}
+
+#[derive(Args)]
+#[command(group(
+    ArgGroup::new("exclusive_group")
+        .args(["in_fq", "in_fa"])
+        .required(true)
+        .multiple(false)
+))]
+pub struct DetectAdapterArgs {
+    #[arg(short = 'I', long)]
+    /// FASTQ path
+    pub in_fq: Option<String>,
+    #[arg(short = 'A', long)]
+    /// FASTA path
+    pub in_fa: Option<String>,
+    #[arg(short = 'n', long)]
+    /// Number of reads (from the start of the file) to sample [default: 100000]
+    pub sample_reads: Option<usize>,
+    #[arg(short = 'k', long, value_parser = clap::value_parser!(u64).range(4..=31))]
+    /// K-mer size (4-31) [default: 12]
+    pub kmer_size: Option<u64>,
+    #[arg(short = 't', long)]
+    /// Length of the 3' end of a read to count the k-mers in [default: 40]
+    pub tail: Option<usize>,
+    #[arg(short = 'r', long, value_parser = validate_ratio)]
+    /// Minimum fraction of the sampled reads with a k-mer of the adapter [default: 0.01]
+    pub min_ratio: Option<f64>,
+    #[arg(long)]
+    /// Output the matched built-in adapter instead of the assembled sequence
+    pub match_builtin: bool,
 }
 
 #[derive(Args)]
@@ -136,8 +832,17 @@ pub struct CompArgs {
     /// Only report unmasked bases [default: false]
     pub exclude_masked: bool,
     #[arg(short = 'r', long)]
-    /// Report bases that overlap with the regions specified in the BED (0-based) file [default: null]
+    /// Report bases that overlap with the regions specified in the BED (0-based), GFF3/GTF (1-based) or RepeatMasker .out file [default: null]
     pub in_bed: Option<String>,
+    #[arg(long, requires = "in_bed")]
+    /// Fail if a sequence name of --in-bed matches no input record, or an interval extends beyond the record
+    pub strict_bed: bool,
+    #[arg(long, requires = "in_bed")]
+    /// Two-column file of sequence name aliases (e.g., `chr1 1`) to match the names of --in-bed with the records
+    pub name_alias: Option<String>,
+    #[arg(long, requires = "in_bed")]
+    /// Match the names of --in-bed with and without the `chr` prefix (and chrM with MT)
+    pub chr_alias: bool,
 }
 
 #[derive(Args)]
@@ -162,6 +867,74 @@ pub struct SampleArgs {
     pub sample_fraction: Option<f64>,
 }
 
+#[derive(Args)]
+#[command(group(
+    ArgGroup::new("exclusive_group")
+        .args(["in_fq", "in_fa"])
+        .required(true)
+        .multiple(false)
+))]
+pub struct SelectArgs {
+    #[arg(short = 'I', long)]
+    /// FASTQ path
+    pub in_fq: Option<String>,
+    #[arg(short = 'A', long)]
+    /// FASTA path
+    pub in_fa: Option<String>,
+    #[arg(short = 't', long)]
+    /// Keep the best reads until their total length reaches TARGET_BASES
+    pub target_bases: usize,
+    #[arg(long)]
+    /// Weight of the length score (w_len) [default: 1.0]
+    pub length_weight: Option<f64>,
+    #[arg(long)]
+    /// Weight of the mean quality score (w_qual) [default: 1.0]
+    pub quality_weight: Option<f64>,
+    #[arg(short = 'a', long)]
+    /// Ascii value [default: 33]
+    pub ascii_base: Option<u8>,
+}
+
+#[derive(Args)]
+#[command(group(
+    ArgGroup::new("exclusive_group")
+        .args(["in_fq", "in_fa"])
+        .required(true)
+        .multiple(false)
+))]
+#[command(group(
+    ArgGroup::new("target_group")
+        .args(["name_list", "in_bed"])
+        .required(true)
+        .multiple(false)
+))]
+pub struct SubseqArgs {
+    #[arg(short = 'I', long)]
+    /// FASTQ path
+    pub in_fq: Option<String>,
+    #[arg(short = 'A', long)]
+    /// FASTA path
+    pub in_fa: Option<String>,
+    #[arg(short = 'n', long)]
+    /// Output the records whose names are listed in the file (one name per line)
+    pub name_list: Option<String>,
+    #[arg(short = 'r', long)]
+    /// Output the regions specified in the BED (0-based) file
+    pub in_bed: Option<String>,
+    #[arg(long, requires = "in_bed")]
+    /// Join the blocks (e.g., exons) of each BED12 feature into one spliced sequence
+    pub split: bool,
+    #[arg(long, requires = "in_bed")]
+    /// Fail if a sequence name of --in-bed matches no input record, or an interval extends beyond the record
+    pub strict_bed: bool,
+    #[arg(long, requires = "in_bed")]
+    /// Two-column file of sequence name aliases (e.g., `chr1 1`) to match the names of --in-bed with the records
+    pub name_alias: Option<String>,
+    #[arg(long, requires = "in_bed")]
+    /// Match the names of --in-bed with and without the `chr` prefix (and chrM with MT)
+    pub chr_alias: bool,
+}
+
 #[derive(Args)]
 #[command(group(
     ArgGroup::new("exclusive_group")
@@ -189,6 +962,18 @@ pub struct SeqArgs {
     #[arg(short = '2', long)]
     /// Output only the reads from even-numbered (2n) records
     pub output_even: bool,
+    #[arg(long)]
+    /// Output only the records whose IDs are listed in the file (one ID per line)
+    pub include_ids: Option<String>,
+    #[arg(long)]
+    /// Drop the records whose IDs are listed in the file (one ID per line)
+    pub exclude_ids: Option<String>,
+    #[arg(long)]
+    /// Output only the records whose header lines (ID and comment) match the regular expression
+    pub header_regex: Option<String>,
+    #[arg(short = 'v', long)]
+    /// Drop the records that match --header-regex instead (effective with --header-regex)
+    pub invert_header_regex: bool,
 
     #[arg(short = 'r', long)]
     /// Reverse complement [default: false]
@@ -235,11 +1020,29 @@ pub struct SeqArgs {
     /// Mask bases by converting them to MASK_CHAR [default: convert to lowercase]
     pub mask_char: Option<char>,
     #[arg(short = 'M', long)]
-    /// Mask bases that overlap with the regions specified in the BED (0-based) file [default: null]
+    /// Mask bases that overlap with the regions specified in the BED (0-based), GFF3/GTF (1-based) or RepeatMasker .out file [default: null]
     pub mask_regions: Option<String>,
     #[arg(long)]
     /// Mask bases that do NOT overlap with the region specified in the BED (effective with --mask-regions / -M)
     pub mask_complement_region: bool,
+    #[arg(long)]
+    /// Mask only the blocks (e.g., exons) of BED12 features in --mask-regions, not the introns
+    pub split: bool,
+    #[arg(long)]
+    /// Fail if a sequence name of --mask-regions matches no input record, or an interval (before --mask-padding) extends beyond the record
+    pub strict_bed: bool,
+    #[arg(long)]
+    /// Two-column file of sequence name aliases (e.g., `chr1 1`) to match the names of --mask-regions with the records
+    pub name_alias: Option<String>,
+    #[arg(long)]
+    /// Match the names of --mask-regions with and without the `chr` prefix (and chrM with MT)
+    pub chr_alias: bool,
+    #[arg(long)]
+    /// Only mask the repeats of the class/family in a RepeatMasker .out file of --mask-regions (e.g., LINE/L1, LINE, Simple_repeat). Can be specified multiple times
+    pub repeat_class: Vec<String>,
+    #[arg(long)]
+    /// Extend each region of --mask-regions by N bases on both sides before masking [default: 0]
+    pub mask_padding: Option<usize>,
 }
 /// Validate seq arguments.
 pub fn valiation_seq_args(args: &SeqArgs) -> Result<(), std::io::Error> {
@@ -250,6 +1053,22 @@ pub fn valiation_seq_args(args: &SeqArgs) -> Result<(), std::io::Error> {
     if args.mask_complement_region && args.mask_regions.is_none() {
         errors.push("--mask-complment-region requires --mask-regions.");
     }
+    if args.split && args.mask_regions.is_none() {
+        errors.push("--split requires --mask-regions.");
+    }
+    if args.strict_bed && args.mask_regions.is_none() {
+        errors.push("--strict-bed requires --mask-regions.");
+    }
+    if (args.name_alias.is_some() || args.chr_alias) && args.mask_regions.is_none() {
+        errors.push("--name-alias and --chr-alias require --mask-regions.");
+    }
+    if (!args.repeat_class.is_empty() || args.mask_padding.is_some()) && args.mask_regions.is_none()
+    {
+        errors.push("--repeat-class and --mask-padding require --mask-regions.");
+    }
+    if args.invert_header_regex && args.header_regex.is_none() {
+        errors.push("--invert-header-regex requires --header-regex.");
+    }
     if args.lowercases_to_char && args.mask_char.is_none() {
         errors.push("--lowercases-to-char requires --mask-char.");
     }
@@ -272,6 +1091,63 @@ pub fn valiation_seq_args(args: &SeqArgs) -> Result<(), std::io::Error> {
     Ok(())
 }
 
+pub fn valiation_bed_args(args: &BedArgs) -> Result<(), std::io::Error> {
+    let mut errors = Vec::new();
+    match args.operation {
+        BedOperation::Intersect | BedOperation::Subtract if args.other_bed.is_none() => {
+            errors.push("intersect and subtract require --other-bed.");
+        }
+        BedOperation::Complement if args.genome.is_none() => {
+            errors.push("complement requires --genome.");
+        }
+        BedOperation::Slop | BedOperation::Flank if args.left.is_none() && args.right.is_none() => {
+            errors.push("slop and flank require --left or --right.");
+        }
+        _ => {}
+    }
+    if !errors.is_empty() {
+        for error in errors {
+            eprintln!("{} {}", "error:".red().bold(), error);
+        }
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+pub fn valiation_qctrim_args(args: &QCTrimArgs) -> Result<(), std::io::Error> {
+    let mut errors = Vec::new();
+    let method = args.method.unwrap_or(TrimMethod::Sum);
+    if method != TrimMethod::Window
+        && (args.window_size.is_some() || args.leading.is_some() || args.trailing.is_some())
+    {
+        errors.push("--window-size, --leading and --trailing require --method window.");
+    }
+    if method != TrimMethod::MaxEe && args.max_ee.is_some() {
+        errors.push("--max-ee requires --method max-ee.");
+    }
+    if !errors.is_empty() {
+        for error in errors {
+            eprintln!("{} {}", "error:".red().bold(), error);
+        }
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+pub fn valiation_trimadapter_args(args: &TrimAdapterArgs) -> Result<(), std::io::Error> {
+    let mut errors = Vec::new();
+    if args.builtin.is_empty() && args.adapter_fa.is_none() && args.in_fq2.is_none() {
+        errors.push("--builtin or --adapter-fa is required unless --in-fq2 is given.");
+    }
+    if !errors.is_empty() {
+        for error in errors {
+            eprintln!("{} {}", "error:".red().bold(), error);
+        }
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
 fn validate_ratio(s: &str) -> Result<f64, String> {
     let val: f64 = s
         .parse()
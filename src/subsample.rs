@@ -1,5 +1,6 @@
 use crate::io_utils::{FaReader, FqReader, FxWriter};
-use crate::sub_cli::SampleArgs;
+use crate::stats::{convert_p_err_to_q_score, Q2PConverter, MAX_Q2P_SCORE};
+use crate::sub_cli::{SampleArgs, SelectArgs};
 use rand::rngs::StdRng;
 use rand::{Rng, SeedableRng};
 
@@ -43,3 +44,202 @@ pub fn subsample_fastx(
     }
     Ok(())
 }
+
+/// Parses FASTQ/A file and keeps the best reads up to a base budget (Filtlong-style selection).
+/// Outputs the results to [`std::io::stdout()`] in the input order.
+///
+/// Each read is scored by its length and mean quality:
+///     `score = w_len * ln(length) + w_qual * ln(meanQ)`
+/// where `meanQ = -10 * log₁₀((P₁ + P₂ + ... + Pₙ) / N)`.
+/// Reads are then taken from the highest score downwards as long as their total length fits `target_bases`.
+/// FASTA records have no quality, so they are scored by length only.
+///
+/// # Arguments
+///
+/// Check the arguments by `--help`
+///
+/// # Errors
+///
+/// Return an error if the operation cannot be completed.
+pub fn select_fastx(
+    fx_path: &str,
+    sparas: &SelectArgs,
+    is_fasta: bool,
+) -> Result<(), std::io::Error> {
+    let asciibase = sparas.ascii_base.unwrap_or(33);
+    let len_weight = sparas.length_weight.unwrap_or(1.0);
+    let qual_weight = sparas.quality_weight.unwrap_or(1.0);
+    let qplookup = Q2PConverter::new(asciibase);
+
+    // 1st pass: score every read
+    let mut scores: Vec<ReadScore> = Vec::new();
+    if is_fasta {
+        let fa_iter = FaReader::new(fx_path)?;
+        for record in fa_iter.records() {
+            match record {
+                Ok(read) => {
+                    let len = read.seq().len();
+                    scores.push(ReadScore {
+                        len,
+                        score: get_score(len, None, len_weight, qual_weight),
+                    });
+                }
+                Err(e) => eprintln!("Error read FASTA: {}", e),
+            }
+        }
+    } else {
+        let fq_iter = FqReader::new(fx_path)?;
+        for record in fq_iter.records() {
+            match record {
+                Ok(read) => {
+                    let len = read.seq().len();
+                    let mean_q = get_mean_qual(read.qual(), asciibase, &qplookup);
+                    scores.push(ReadScore {
+                        len,
+                        score: get_score(len, Some(mean_q), len_weight, qual_weight),
+                    });
+                }
+                Err(e) => eprintln!("Error read FASTQ: {}", e),
+            }
+        }
+    }
+    let is_selected = select_by_score(&scores, sparas.target_bases);
+
+    // 2nd pass: output the selected reads
+    let mut fx_writer = FxWriter::new(is_fasta);
+    if is_fasta {
+        let fa_iter = FaReader::new(fx_path)?;
+        for (read, _) in fa_iter
+            .records()
+            .filter_map(|r| r.ok())
+            .zip(is_selected.iter())
+            .filter(|(_, &b)| b)
+        {
+            fx_writer.write(read.id(), read.seq(), read.desc(), &[])?;
+        }
+    } else {
+        let fq_iter = FqReader::new(fx_path)?;
+        for (read, _) in fq_iter
+            .records()
+            .filter_map(|r| r.ok())
+            .zip(is_selected.iter())
+            .filter(|(_, &b)| b)
+        {
+            fx_writer.write(read.id(), read.seq(), read.desc(), read.qual())?;
+        }
+    }
+    Ok(())
+}
+
+struct ReadScore {
+    len: usize,
+    score: f64,
+}
+
+/// `w_len * ln(length) + w_qual * ln(meanQ)`, or the length term only without a quality.
+/// An empty read and a mean quality of 0 are clamped, so that the score stays finite (not `-inf` or `NaN` by a zero weight).
+fn get_score(len: usize, mean_q: Option<f64>, len_weight: f64, qual_weight: f64) -> f64 {
+    let len_score = len_weight * (len.max(1) as f64).ln();
+    match mean_q {
+        Some(mean_q) => len_score + qual_weight * mean_q.max(f64::MIN_POSITIVE).ln(),
+        None => len_score,
+    }
+}
+
+/// Phred-scaled mean error probability of the read. Return 0 for an empty read.
+/// A quality below `asciibase` counts as Q0 and one beyond the table of `qplookup` as its highest value.
+fn get_mean_qual(qual: &[u8], asciibase: u8, qplookup: &Q2PConverter) -> f64 {
+    if qual.is_empty() {
+        return 0.0;
+    }
+    let sum: f64 = qual
+        .iter()
+        .map(|&q| qplookup.get_prob(q.clamp(asciibase, asciibase.saturating_add(MAX_Q2P_SCORE))))
+        .sum();
+    f64::abs(convert_p_err_to_q_score(sum / qual.len() as f64))
+}
+
+/// Greedily pick reads from the highest score until `target_bases` is reached.
+/// Reads that do not fit the remaining budget are skipped. Return a mask in the input order.
+fn select_by_score(scores: &[ReadScore], target_bases: usize) -> Vec<bool> {
+    let mut order: Vec<usize> = (0..scores.len()).collect();
+    order.sort_by(|&a, &b| scores[b].score.total_cmp(&scores[a].score));
+
+    let mut is_selected = vec![false; scores.len()];
+    let mut total: usize = 0;
+    for idx in order {
+        if total + scores[idx].len <= target_bases {
+            total += scores[idx].len;
+            is_selected[idx] = true;
+        }
+    }
+    is_selected
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_mean_qual() {
+        let qplookup = Q2PConverter::new(33);
+        assert_eq!(get_mean_qual(b"++++", 33, &qplookup), 10.0);
+        assert!((get_mean_qual(b"+5", 33, &qplookup) - 12.596).abs() < 1e-3);
+        assert_eq!(get_mean_qual(b"", 33, &qplookup), 0.0);
+        // out of the range of the quality scores
+        let qplookup = Q2PConverter::new(64);
+        assert_eq!(get_mean_qual(b"!!!!", 64, &qplookup), 0.0);
+        assert_eq!(get_mean_qual(b"@@", 64, &qplookup), 0.0);
+        assert!(get_mean_qual(b"\xff", 64, &qplookup) > 170.0);
+    }
+
+    #[test]
+    fn test_get_score() {
+        assert!(
+            (get_score(100, Some(10.0), 1.0, 2.0) - (100f64.ln() + 2.0 * 10f64.ln())).abs() < 1e-9
+        );
+        assert_eq!(get_score(100, None, 1.0, 2.0), 100f64.ln());
+        // an empty read or all Q0 with a zero weight
+        assert_eq!(get_score(0, Some(20.0), 0.0, 1.0), 20f64.ln());
+        assert_eq!(get_score(0, Some(20.0), 1.0, 0.0), 0.0);
+        assert_eq!(get_score(100, Some(0.0), 1.0, 0.0), 100f64.ln());
+        assert!(get_score(100, Some(0.0), 1.0, 1.0).is_finite());
+        // a read of all Q0 is not selected before a longer one (NaN would rank first)
+        let scores = [
+            ReadScore {
+                len: 10,
+                score: get_score(10, Some(0.0), 1.0, 0.0),
+            },
+            ReadScore {
+                len: 20,
+                score: get_score(20, Some(30.0), 1.0, 0.0),
+            },
+        ];
+        assert_eq!(select_by_score(&scores, 20), vec![false, true]);
+    }
+
+    #[test]
+    fn test_select_by_score() {
+        let scores = [
+            ReadScore {
+                len: 100,
+                score: 3.0,
+            },
+            ReadScore {
+                len: 500,
+                score: 5.0,
+            },
+            ReadScore {
+                len: 300,
+                score: 4.0,
+            },
+            ReadScore {
+                len: 50,
+                score: 1.0,
+            },
+        ];
+        assert_eq!(select_by_score(&scores, 850), vec![false, true, true, true]);
+        assert_eq!(select_by_score(&scores, 900), vec![true, true, true, false]);
+        assert_eq!(select_by_score(&scores, 10), vec![false; 4]);
+    }
+}
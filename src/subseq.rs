@@ -0,0 +1,167 @@
+use crate::alias::NameAlias;
+use crate::bed::{BedFeatureMap, BedNameCheck, BedPos, Strand};
+use crate::dna;
+use crate::io_utils::{read_name_set, FaReader, FqReader, FxWriter};
+use crate::record::RecordType;
+use crate::sub_cli::SubseqArgs;
+use std::collections::HashSet;
+
+/// Parses FASTA/Q file and extracts the sequences or regions listed in a name list or a BED file.
+/// Outputs the results to [`std::io::stdout()`] in FASTA/Q format.
+///
+/// * With a name list, the whole records whose names are in the list are output as they are.
+/// * With a BED (0-based) file, each interval is output as a separate record.
+///   The record is named after the BED name column (BED4+), or `chrom:start-end` if there is no name,
+///   where `start-end` is 1-based and inclusive (e.g., `chr1 99 200` is named `chr1:100-200`).
+///   Intervals on the minus strand (BED6) are reverse-complemented, and `(-)` is appended to unnamed ones.
+///   The quality of FASTQ records is sliced (and reversed) together with the sequence.
+///   Intervals are output in the BED order and are clipped to the length of the sequence.
+///   With `--split`, the blocks (e.g., exons) of each BED12 feature are joined into one spliced sequence.
+///   BED names are matched with the records by `--name-alias` and `--chr-alias` as well, and unmatched ones are warned about.
+///   With `--strict-bed`, it fails if a BED sequence name matches no record or an interval extends beyond the record.
+///
+/// # Arguments
+///
+/// Check the arguments by `--help`
+///
+/// # Errors
+///
+/// Return an error if the operation cannot be completed.
+pub fn subseq_fastx(
+    fx_path: &str,
+    sparas: &SubseqArgs,
+    is_fasta: bool,
+) -> Result<(), std::io::Error> {
+    let target = match (&sparas.name_list, &sparas.in_bed) {
+        (Some(list), _) => Target::Names(read_name_set(list)?),
+        (None, Some(bed)) => {
+            let alias = NameAlias::from(sparas.name_alias.as_deref(), sparas.chr_alias)?;
+            Target::Regions(BedFeatureMap::from(bed)?.with_alias(alias))
+        }
+        (None, None) => unreachable!("subseq requires --name-list or --in-bed"),
+    };
+    let mut name_check = sparas
+        .in_bed
+        .as_ref()
+        .map(|bed| BedNameCheck::new(bed, sparas.strict_bed));
+    let mut fx_writer = FxWriter::new(is_fasta);
+    if is_fasta {
+        let fa_iter = FaReader::new(fx_path)?;
+        for record in fa_iter.records() {
+            match record {
+                Ok(read) => {
+                    check_regions(&mut name_check, &target, &read)?;
+                    write_subseq(&mut fx_writer, &read, &target, sparas.split, true)?
+                }
+                Err(e) => eprintln!("Error read FASTA: {}", e),
+            }
+        }
+    } else {
+        let fq_iter = FqReader::new(fx_path)?;
+        for record in fq_iter.records() {
+            match record {
+                Ok(read) => {
+                    check_regions(&mut name_check, &target, &read)?;
+                    write_subseq(&mut fx_writer, &read, &target, sparas.split, false)?
+                }
+                Err(e) => eprintln!("Error read FASTQ: {}", e),
+            }
+        }
+    }
+    if let (Some(check), Target::Regions(feature_map)) = (&name_check, &target) {
+        check.finish(feature_map.iter().map(|(name, _)| name))?;
+    }
+    Ok(())
+}
+
+enum Target {
+    Names(HashSet<String>),
+    Regions(BedFeatureMap),
+}
+
+fn check_regions(
+    name_check: &mut Option<BedNameCheck>,
+    target: &Target,
+    read: &dyn RecordType,
+) -> Result<(), std::io::Error> {
+    if let (Some(check), Target::Regions(feature_map)) = (name_check.as_mut(), target) {
+        check.check(read.id(), feature_map.max_end(read.id()), read.seq().len())?;
+    }
+    Ok(())
+}
+
+fn write_subseq(
+    fx_writer: &mut FxWriter,
+    read: &dyn RecordType,
+    target: &Target,
+    split: bool,
+    is_fasta: bool,
+) -> Result<(), std::io::Error> {
+    let qual: &[u8] = if is_fasta { &[] } else { read.qual() };
+    match target {
+        Target::Names(names) => {
+            if names.contains(read.id()) {
+                fx_writer.write(read.id(), read.seq(), read.desc(), qual)?;
+            }
+        }
+        Target::Regions(feature_map) => {
+            if let Some(features) = feature_map.get(read.id()) {
+                for feature in features {
+                    if let Some((start, end)) = clip_region(feature.pos, read.seq().len()) {
+                        let is_reverse = feature.strand == Strand::Reverse;
+                        let name = match &feature.name {
+                            Some(name) => name.clone(),
+                            None if is_reverse => {
+                                format!("{}:{}-{}(-)", read.id(), start + 1, end)
+                            }
+                            None => format!("{}:{}-{}", read.id(), start + 1, end),
+                        };
+                        let blocks = match &feature.blocks {
+                            Some(blocks) if split => blocks.clone(),
+                            _ => vec![BedPos(start, end)],
+                        };
+                        let (mut sub_seq, mut sub_qual) = (Vec::new(), Vec::new());
+                        for (s, e) in blocks
+                            .into_iter()
+                            .filter_map(|block| clip_region(block, read.seq().len()))
+                        {
+                            sub_seq.extend_from_slice(&read.seq()[s..e]);
+                            if !is_fasta {
+                                sub_qual.extend_from_slice(&qual[s..e]);
+                            }
+                        }
+                        if is_reverse {
+                            dna::revcomp(&mut sub_seq);
+                            sub_qual.reverse();
+                        }
+                        fx_writer.write(&name, &sub_seq, None, &sub_qual)?;
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Clip the region to `[0, len)`. Return `None` if nothing is left.
+fn clip_region(region: BedPos, len: usize) -> Option<(usize, usize)> {
+    let end = region.1.min(len);
+    if region.0 < end {
+        Some((region.0, end))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clip_region() {
+        assert_eq!(clip_region(BedPos(2, 5), 10), Some((2, 5)));
+        assert_eq!(clip_region(BedPos(2, 15), 10), Some((2, 10)));
+        assert_eq!(clip_region(BedPos(10, 15), 10), None);
+        assert_eq!(clip_region(BedPos(5, 5), 10), None);
+    }
+}
@@ -1,8 +1,10 @@
 use crate::io_utils::{FqReader, FxWriter};
+use crate::stats::{Q2PConverter, MAX_Q2P_SCORE};
+use crate::sub_cli::{PolyBase, QCTrimArgs, TrimMethod};
 /// Trims low-quality bases from a FASTQ data based on a quality threshold `Q`.
 /// Outputs the FASTQ to [`std::io::stdout()`].
 ///
-/// The algorithm:
+/// The algorithm (`--method sum`, the default; see [`QualTrimmer`] for the others):
 ///
 /// (1) Scan the read from 5' to 3' to find the first base with `quality >= Q`.
 ///     This is the starting position of the trimmed read.
@@ -14,13 +16,19 @@ use crate::io_utils::{FqReader, FxWriter};
 /// Note:
 /// (1) If all base quality are less than `Q`, the read is discarded.
 /// (2) If the trimmed read is shorter than `min_len`, we first extend it from the 3' end. If still too short, extend form the 5' end until `min_len` is reached or no more bases are available.
+///     With `--discard-short`, such reads are discarded instead.
+/// (3) Fixed-position trimming combines with quality trimming in this order:
+///     `--trim-left`/`--trim-right` on the raw read, quality trimming (and the `min_len` extension) on the rest,
+///     then `--max-length` (keep at most the first bases) or `--truncate-to` (exactly the length, shorter reads are discarded).
+///     The qualities are cut at the same positions as the bases.
+/// (4) With `--poly-x`, the homopolymer tail (see [`PolyTail`]) is removed after the fixed-position trimming
+///     and before quality trimming, as high-quality poly-G runs pass the quality threshold.
 ///
 ///
 /// # Arguments
 ///
-/// * `path` - FASTQ path
-/// * `q_plus_ascii` - The sum of quality threshold and asciibase.
-/// * `minilen` - The minimum length of read.
+/// * `fq_path` - FASTQ path
+/// * `tparas` - Check the other arguments by `--help`
 ///
 /// # Errors
 ///
@@ -34,42 +42,290 @@ use crate::io_utils::{FqReader, FxWriter};
 /// * Illumina reads may benefit from light trimming low-quality tails at 3' end.
 /// * Long-read technologies are generally not necessary to do quality trimming, because it may remove informative regions and reduce read length unnecessarily.
 ///
-pub fn trimfq(fq_path: &str, q_plus_ascii: u8, minlen: usize) -> Result<(), std::io::Error> {
+pub fn qctrim_fastq(fq_path: &str, tparas: &QCTrimArgs) -> Result<(), std::io::Error> {
+    let trimmer = build_trimmer(tparas);
+    let poly_tail = PolyTail::from(tparas.poly_x, tparas.poly_min_len, tparas.poly_error_rate);
+    let minlen = tparas.min_length.unwrap_or(30);
     let reader = FqReader::new(fq_path)?;
     let mut writer = FxWriter::new(false);
-    let (mut start, mut end, mut size): (usize, usize, usize);
     for record in reader.records() {
         let read = record.unwrap();
-        (start, end) = trim_read_by_q(read.qual(), q_plus_ascii);
-
-        // discard read if start == end (Q < q_threshold for all bases)
-        if start < end {
-            size = read.qual().len();
-
-            if size < minlen {
-                // write full length is size < minlen
-                (start, end) = (0, size - 1);
-            } else if minlen > (end - start + 1) {
-                if size - start >= minlen {
-                    // append 3' seq to reach the minlen
-                    end = start + minlen - 1;
-                } else {
-                    // append 5' seq if the whole 3' end is not enough
-                    end = size - 1;
-                    start = size - minlen;
-                }
+        // fixed-position trimming
+        let size = read.qual().len();
+        let left = tparas.trim_left.unwrap_or(0).min(size);
+        let mut right = size
+            .saturating_sub(tparas.trim_right.unwrap_or(0))
+            .max(left);
+        if let Some(poly_tail) = &poly_tail {
+            right = left + poly_tail.tail_start(&read.seq()[left..right]);
+        }
+        let Some((start, end)) = trim_range(
+            &read.qual()[left..right],
+            trimmer.as_ref(),
+            minlen,
+            tparas.discard_short,
+        ) else {
+            continue;
+        };
+        let (start, mut end) = (left + start, left + end);
+        // length capping
+        if let Some(max_len) = tparas.max_length {
+            end = end.min(start + max_len);
+        }
+        if let Some(len) = tparas.truncate_to {
+            if end - start < len {
+                continue;
             }
+            end = start + len;
+        }
+        if start < end {
             writer.write(
                 read.id(),
-                &read.seq()[start..(end + 1)],
+                &read.seq()[start..end],
                 read.desc(),
-                &read.qual()[start..(end + 1)],
+                &read.qual()[start..end],
             )?;
         }
     }
     Ok(())
 }
 
+/// Trims low-quality bases from a FASTQ data by the running sum only (see [`qctrim_fastq`]).
+/// Outputs the FASTQ to [`std::io::stdout()`].
+///
+/// # Arguments
+///
+/// * `fq_path` - FASTQ path
+/// * `q_plus_ascii` - The sum of quality threshold and asciibase.
+/// * `minlen` - The minimum length of read.
+///
+/// # Errors
+///
+/// Return an error if the operation cannot be completed.
+pub fn trimfq(fq_path: &str, q_plus_ascii: u8, minlen: usize) -> Result<(), std::io::Error> {
+    let tparas = QCTrimArgs {
+        in_fq: fq_path.to_string(),
+        q_thershold: Some(q_plus_ascii),
+        min_length: Some(minlen),
+        ascii_base: Some(0),
+        trim_left: None,
+        trim_right: None,
+        max_length: None,
+        truncate_to: None,
+        method: Some(TrimMethod::Sum),
+        window_size: None,
+        leading: None,
+        trailing: None,
+        max_ee: None,
+        discard_short: false,
+        poly_x: None,
+        poly_min_len: None,
+        poly_error_rate: None,
+    };
+    qctrim_fastq(fq_path, &tparas)
+}
+
+/// Trimming of the 3' homopolymer tail (e.g., poly-G of two-colour chemistry or poly-A).
+///
+/// A tail starts with the base, has at least `min_len` bases, and has at most `floor(length * error_rate)` mismatches.
+/// For [`PolyBase::X`], the longest tail of any base is removed.
+pub struct PolyTail {
+    base: PolyBase,
+    min_len: usize,
+    error_rate: f64,
+}
+impl PolyTail {
+    /// `None` if no base is given.
+    pub fn from(
+        base: Option<PolyBase>,
+        min_len: Option<usize>,
+        error_rate: Option<f64>,
+    ) -> Option<Self> {
+        Some(PolyTail {
+            base: base?,
+            min_len: min_len.unwrap_or(10).max(1),
+            error_rate: error_rate.unwrap_or(0.1),
+        })
+    }
+    /// The start of the tail, or the length of the sequence if there is no tail.
+    pub fn tail_start(&self, seq: &[u8]) -> usize {
+        let bases: &[u8] = match self.base {
+            PolyBase::A => b"A",
+            PolyBase::C => b"C",
+            PolyBase::G => b"G",
+            PolyBase::T => b"T",
+            PolyBase::X => b"ACGT",
+        };
+        bases
+            .iter()
+            .map(|&b| self.tail_start_of(seq, b))
+            .min()
+            .unwrap_or(seq.len())
+    }
+    fn tail_start_of(&self, seq: &[u8], base: u8) -> usize {
+        let mut start = seq.len();
+        let mut mismatches = 0;
+        for i in (0..seq.len()).rev() {
+            let max_mismatches = ((seq.len() - i) as f64 * self.error_rate) as usize;
+            if seq[i].to_ascii_uppercase() == base {
+                if mismatches <= max_mismatches {
+                    start = i;
+                }
+            } else {
+                mismatches += 1;
+                // more than one mismatch over the limit is not made up for by the following bases
+                if mismatches > max_mismatches + 1 {
+                    break;
+                }
+            }
+        }
+        if seq.len() - start >= self.min_len {
+            start
+        } else {
+            seq.len()
+        }
+    }
+}
+
+/// A quality-trimming algorithm.
+pub trait QualTrimmer {
+    /// The range (half-open) of the qualities to keep. An empty range discards the read.
+    fn trim(&self, qual: &[u8]) -> (usize, usize);
+}
+
+/// The running-sum algorithm of seqtk `trimfq` (see [`trimfq`]).
+pub struct RunningSum {
+    pub q_plus_ascii: u8,
+}
+impl QualTrimmer for RunningSum {
+    fn trim(&self, qual: &[u8]) -> (usize, usize) {
+        let (start, end) = trim_read_by_q(qual, self.q_plus_ascii);
+        // discard read if start == end (Q < q_threshold for all bases)
+        if start < end {
+            (start, end + 1)
+        } else {
+            (start, start)
+        }
+    }
+}
+
+/// Trimmomatic-style LEADING, TRAILING and SLIDINGWINDOW, in this order.
+///
+/// (1) Remove the bases with `quality < leading` from the 5' end, and those with `quality < trailing` from the 3' end.
+/// (2) Scan the windows of `window` bases from 5' to 3', and cut the read at the start of the first window
+///     whose mean quality is below `Q`. A read shorter than the window is checked as one window.
+pub struct SlidingWindow {
+    pub q_plus_ascii: u8,
+    pub leading_plus_ascii: u8,
+    pub trailing_plus_ascii: u8,
+    pub window: usize,
+}
+impl QualTrimmer for SlidingWindow {
+    fn trim(&self, qual: &[u8]) -> (usize, usize) {
+        let start = qual
+            .iter()
+            .position(|&q| q >= self.leading_plus_ascii)
+            .unwrap_or(qual.len());
+        let end = qual
+            .iter()
+            .rposition(|&q| q >= self.trailing_plus_ascii)
+            .map_or(start, |i| (i + 1).max(start));
+        if start == end {
+            return (start, end);
+        }
+        let window = self.window.clamp(1, end - start);
+        // mean < Q  <=>  sum < Q * window
+        let threshold = self.q_plus_ascii as usize * window;
+        let cut = (start..=end - window).find(|&i| {
+            qual[i..i + window]
+                .iter()
+                .map(|&q| q as usize)
+                .sum::<usize>()
+                < threshold
+        });
+        (start, cut.unwrap_or(end))
+    }
+}
+
+/// Truncation once the cumulative expected errors (the sum of the error probabilities from the 5' end) exceed `max_ee`,
+/// as `-fastq_truncee` of USEARCH. The base making the sum exceed `max_ee` is removed.
+/// A quality below `ascii_base` counts as Q0 and one beyond the table of `q2p` as its highest value.
+pub struct MaxExpectedErrors {
+    pub max_ee: f64,
+    pub ascii_base: u8,
+    pub q2p: Q2PConverter,
+}
+impl QualTrimmer for MaxExpectedErrors {
+    fn trim(&self, qual: &[u8]) -> (usize, usize) {
+        let mut expected_errors = 0.0;
+        for (i, &q) in qual.iter().enumerate() {
+            let q = q.clamp(
+                self.ascii_base,
+                self.ascii_base.saturating_add(MAX_Q2P_SCORE),
+            );
+            expected_errors += self.q2p.get_prob(q);
+            if expected_errors > self.max_ee {
+                return (0, i);
+            }
+        }
+        (0, qual.len())
+    }
+}
+
+fn build_trimmer(tparas: &QCTrimArgs) -> Box<dyn QualTrimmer> {
+    let asciibase = tparas.ascii_base.unwrap_or(33);
+    let q_plus_ascii = tparas.q_thershold.unwrap_or(13) + asciibase;
+    match tparas.method.unwrap_or(TrimMethod::Sum) {
+        TrimMethod::Sum => Box::new(RunningSum { q_plus_ascii }),
+        TrimMethod::Window => Box::new(SlidingWindow {
+            q_plus_ascii,
+            leading_plus_ascii: tparas.leading.unwrap_or(0) + asciibase,
+            trailing_plus_ascii: tparas.trailing.unwrap_or(0) + asciibase,
+            window: tparas.window_size.unwrap_or(4),
+        }),
+        TrimMethod::MaxEe => Box::new(MaxExpectedErrors {
+            max_ee: tparas.max_ee.unwrap_or(1.0),
+            ascii_base: asciibase,
+            q2p: Q2PConverter::new(asciibase),
+        }),
+    }
+}
+
+/// The range (half-open) of the read kept by the trimmer, with the extension to `minlen`
+/// (or `None` for a read shorter than `minlen` if `discard_short`).
+/// Return `None` if the read is discarded.
+fn trim_range(
+    qual: &[u8],
+    trimmer: &dyn QualTrimmer,
+    minlen: usize,
+    discard_short: bool,
+) -> Option<(usize, usize)> {
+    let (mut start, mut end) = trimmer.trim(qual);
+    if start >= end {
+        return None;
+    }
+    let size = qual.len();
+
+    if discard_short {
+        if end - start < minlen {
+            return None;
+        }
+    } else if size < minlen {
+        // write full length is size < minlen
+        (start, end) = (0, size);
+    } else if minlen > (end - start) {
+        if size - start >= minlen {
+            // append 3' seq to reach the minlen
+            end = start + minlen;
+        } else {
+            // append 5' seq if the whole 3' end is not enough
+            end = size;
+            start = size - minlen;
+        }
+    }
+    Some((start, end))
+}
+
 /// return start, end
 fn trim_read_by_q(qual: &[u8], q_plus_ascii: u8) -> (usize, usize) {
     let mut start: usize = 0;
@@ -117,4 +373,71 @@ mod tests {
         let (start, end) = trim_read_by_q(qual, 33 + 20);
         assert_eq!(end - start + 1, 140);
     }
+
+    #[test]
+    fn test_trim_range() {
+        let qual = b"##IIIIIIII##";
+        let trimmer = RunningSum {
+            q_plus_ascii: 33 + 20,
+        };
+        assert_eq!(trim_range(qual, &trimmer, 0, false), Some((2, 10)));
+        // extended to minlen from the 3' end, then from the 5' end
+        assert_eq!(trim_range(qual, &trimmer, 9, false), Some((2, 11)));
+        assert_eq!(trim_range(qual, &trimmer, 11, false), Some((1, 12)));
+        assert_eq!(trim_range(qual, &trimmer, 9, true), None);
+        assert_eq!(trim_range(b"####", &trimmer, 0, false), None);
+    }
+
+    #[test]
+    fn test_sliding_window() {
+        // Q: 2 2 40 40 40 40 40 10 40 10 10 10 2
+        let qual = b"##IIIII+I+++#";
+        let trimmer = SlidingWindow {
+            q_plus_ascii: 33 + 20,
+            leading_plus_ascii: 33 + 3,
+            trailing_plus_ascii: 33 + 3,
+            window: 4,
+        };
+        // the window at 6 (40 10 40 10) has the mean 25, and the window at 7 (10 40 10 10) has 17.5
+        assert_eq!(trimmer.trim(qual), (2, 7));
+        // leading and trailing only
+        let trimmer = SlidingWindow {
+            q_plus_ascii: 33,
+            ..trimmer
+        };
+        assert_eq!(trimmer.trim(qual), (2, 12));
+        assert_eq!(trimmer.trim(b"###"), (3, 3));
+    }
+
+    #[test]
+    fn test_poly_tail() {
+        let poly_g = PolyTail::from(Some(PolyBase::G), Some(5), None).unwrap();
+        assert_eq!(poly_g.tail_start(b"ACGTACATGGGGGGGGGG"), 8);
+        // one mismatch in 10 bases
+        assert_eq!(poly_g.tail_start(b"ACGTACGTAGGGGTGGGGG"), 9);
+        // the terminal mismatch is tolerated, but the tail starts with G
+        assert_eq!(poly_g.tail_start(b"ACGTACTTTGGGGGGGGGA"), 9);
+        assert_eq!(poly_g.tail_start(b"ACGTACGTGGGG"), 12);
+        assert_eq!(poly_g.tail_start(b""), 0);
+        let poly_x = PolyTail::from(Some(PolyBase::X), Some(5), Some(0.0)).unwrap();
+        assert_eq!(poly_x.tail_start(b"ACGTACGTaaaaaaa"), 8);
+        assert!(PolyTail::from(None, Some(5), None).is_none());
+    }
+
+    #[test]
+    fn test_max_expected_errors() {
+        let trimmer = MaxExpectedErrors {
+            max_ee: 0.25,
+            ascii_base: 33,
+            q2p: Q2PConverter::new(33),
+        };
+        // Q10 (0.1) Q10 (0.1) Q20 (0.01) Q10 (0.1)
+        assert_eq!(trimmer.trim(b"+++5+"), (0, 2));
+        assert_eq!(trimmer.trim(b"++5+"), (0, 3));
+        assert_eq!(trimmer.trim(b"II"), (0, 2));
+        // out of the range of the quality scores
+        assert_eq!(trimmer.trim(b"I!I"), (0, 1));
+        assert_eq!(trimmer.trim(b"I\x20I"), (0, 1));
+        assert_eq!(trimmer.trim(b"I\xffI"), (0, 3));
+    }
 }
@@ -0,0 +1,224 @@
+use crate::io_utils::{FqReader, FxWriter};
+use crate::sub_cli::UmiArgs;
+use bio::io::fastq;
+
+/// Parses FASTQ files (read 1, and optionally read 2 and an index read) and moves the UMIs to the read names.
+/// Outputs the reads (interleaved pairs, or to `--out1` and `--out2`) to [`std::io::stdout()`],
+/// and the numbers of reads to [`std::io::stderr()`].
+///
+/// The UMI bases (`N` of the pattern) and the skipped bases (`X`) are removed from the 5' end with their qualities,
+/// and the UMI is appended to the name of every read of a pair (`<name><separator><UMI>`).
+/// With `--umi-fq`, the UMI is taken from the index read and the other reads are kept as they are.
+///
+/// # Arguments
+///
+/// Check the arguments by `--help`
+///
+/// # Errors
+///
+/// Return an error if a pattern is invalid, the files have different numbers of reads, or the operation cannot be completed.
+pub fn umi_extract(uparas: &UmiArgs) -> Result<(), std::io::Error> {
+    let pattern = UmiPattern::new(&uparas.pattern)?;
+    let pattern2 = match &uparas.pattern2 {
+        Some(p) => Some(UmiPattern::new(p)?),
+        None => None,
+    };
+    let separator = uparas.separator.as_deref().unwrap_or("_");
+    let (mut writer1, mut writer2) = match (&uparas.out1, &uparas.out2) {
+        (Some(out1), Some(out2)) => (
+            FxWriter::from_path(out1, false)?,
+            Some(FxWriter::from_path(out2, false)?),
+        ),
+        _ => (FxWriter::new(false), None),
+    };
+    let mut fq2_iter = match &uparas.in_fq2 {
+        Some(path) => Some((path, FqReader::new(path)?.records())),
+        None => None,
+    };
+    let mut umi_iter = match &uparas.umi_fq {
+        Some(path) => Some((path, FqReader::new(path)?.records())),
+        None => None,
+    };
+    let (mut total, mut discarded) = (0, 0);
+    for record1 in FqReader::new(&uparas.in_fq)?.records() {
+        let read2 = next_mate(&uparas.in_fq, fq2_iter.as_mut())?;
+        let umi_read = next_mate(&uparas.in_fq, umi_iter.as_mut())?;
+        let read1 = match record1 {
+            Ok(read) => read,
+            Err(e) => {
+                eprintln!("Error read FASTQ: {}", e);
+                continue;
+            }
+        };
+        total += 1;
+        // the UMI of the index read, or of read 1 (and read 2)
+        let mut umi = Umi::default();
+        let (start1, start2) = match &umi_read {
+            Some(index) => (pattern.extract(index, &mut umi).map(|_| 0), Some(0)),
+            None => (
+                pattern.extract(&read1, &mut umi),
+                match (&pattern2, &read2) {
+                    (Some(pattern2), Some(read2)) => pattern2.extract(read2, &mut umi),
+                    _ => Some(0),
+                },
+            ),
+        };
+        let (Some(start1), Some(start2)) = (start1, start2) else {
+            discarded += 1;
+            continue;
+        };
+        umi.write(&mut writer1, &read1, start1, separator, uparas.umi_qual)?;
+        if let Some(read2) = &read2 {
+            let writer = writer2.as_mut().unwrap_or(&mut writer1);
+            umi.write(writer, read2, start2, separator, uparas.umi_qual)?;
+        }
+    }
+    for (path, mut iter) in [fq2_iter, umi_iter].into_iter().flatten() {
+        if iter.next().is_some() {
+            return Err(unpaired(&uparas.in_fq, path));
+        }
+    }
+    eprintln!(
+        "Reads: {}, with UMIs: {}, discarded (shorter than the pattern): {}",
+        total,
+        total - discarded,
+        discarded
+    );
+    Ok(())
+}
+
+type FqRecords = fastq::Records<std::io::BufReader<Box<dyn std::io::BufRead>>>;
+
+/// The next record of the mate file (if any), which must not end before read 1.
+fn next_mate(
+    fq1_path: &str,
+    mate: Option<&mut (&String, FqRecords)>,
+) -> Result<Option<fastq::Record>, std::io::Error> {
+    let Some((path, iter)) = mate else {
+        return Ok(None);
+    };
+    match iter.next() {
+        Some(Ok(read)) => Ok(Some(read)),
+        Some(Err(e)) => Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            e.to_string(),
+        )),
+        None => Err(unpaired(fq1_path, path)),
+    }
+}
+
+fn unpaired(fq1_path: &str, path: &str) -> std::io::Error {
+    std::io::Error::new(
+        std::io::ErrorKind::InvalidData,
+        format!("{} and {} have different numbers of reads", fq1_path, path),
+    )
+}
+
+/// The UMI bases and qualities of a read (pair).
+#[derive(Default)]
+struct Umi {
+    seq: Vec<u8>,
+    qual: Vec<u8>,
+}
+impl Umi {
+    /// Write the read without the first `start` bases, named with the UMI.
+    fn write(
+        &self,
+        writer: &mut FxWriter,
+        read: &fastq::Record,
+        start: usize,
+        separator: &str,
+        umi_qual: bool,
+    ) -> Result<(), std::io::Error> {
+        let id = format!(
+            "{}{}{}",
+            read.id(),
+            separator,
+            String::from_utf8_lossy(&self.seq)
+        );
+        let desc = if umi_qual {
+            let tag = format!("UQ:Z:{}", String::from_utf8_lossy(&self.qual));
+            Some(match read.desc() {
+                Some(desc) => format!("{} {}", desc, tag),
+                None => tag,
+            })
+        } else {
+            read.desc().map(|d| d.to_string())
+        };
+        writer.write(
+            &id,
+            &read.seq()[start..],
+            desc.as_deref(),
+            &read.qual()[start..],
+        )
+    }
+}
+
+/// A pattern of `N` (UMI base) and `X` (skipped base) at the 5' end.
+struct UmiPattern(Vec<bool>);
+impl UmiPattern {
+    fn new(pattern: &str) -> Result<Self, std::io::Error> {
+        let is_umi: Vec<bool> = pattern
+            .bytes()
+            .filter_map(|b| match b.to_ascii_uppercase() {
+                b'N' => Some(true),
+                b'X' => Some(false),
+                _ => None,
+            })
+            .collect();
+        if is_umi.len() != pattern.len() || !is_umi.contains(&true) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!(
+                    "invalid UMI pattern '{}': only N (UMI) and X (skip) are allowed, with at least one N",
+                    pattern
+                ),
+            ));
+        }
+        Ok(UmiPattern(is_umi))
+    }
+    /// Append the UMI of the read to `umi` and return the length of the pattern,
+    /// or `None` if the read is shorter than the pattern.
+    fn extract(&self, read: &fastq::Record, umi: &mut Umi) -> Option<usize> {
+        if read.seq().len() < self.0.len() {
+            return None;
+        }
+        for (i, &is_umi) in self.0.iter().enumerate() {
+            if is_umi {
+                umi.seq.push(read.seq()[i]);
+                umi.qual.push(read.qual()[i]);
+            }
+        }
+        Some(self.0.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_umi_pattern() {
+        let read = fastq::Record::with_attrs("r1", None, b"ACGTACGTTTTGGCC", b"ABCDEFGHIJKLMNO");
+        let pattern = UmiPattern::new("NNNNXXNN").unwrap();
+        let mut umi = Umi::default();
+        assert_eq!(pattern.extract(&read, &mut umi), Some(8));
+        assert_eq!(umi.seq, b"ACGTGT".to_vec());
+        assert_eq!(umi.qual, b"ABCDGH".to_vec());
+        // the UMI of read 2 is appended
+        assert_eq!(
+            UmiPattern::new("xN").unwrap().extract(&read, &mut umi),
+            Some(2)
+        );
+        assert_eq!(umi.seq, b"ACGTGTC".to_vec());
+        assert_eq!(
+            UmiPattern::new(&"N".repeat(16))
+                .unwrap()
+                .extract(&read, &mut umi),
+            None
+        );
+        assert!(UmiPattern::new("NNAX").is_err());
+        assert!(UmiPattern::new("XXX").is_err());
+        assert!(UmiPattern::new("").is_err());
+    }
+}
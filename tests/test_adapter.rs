@@ -0,0 +1,144 @@
+#[cfg(test)]
+mod tests {
+    use std::process::Command;
+    use std::{fs, str};
+
+    fn run_program_with_args(args: &[&str]) -> String {
+        let output = Command::new("cargo")
+            .arg("run")
+            .args(args)
+            .output()
+            .expect("Failed to execute command");
+
+        str::from_utf8(&output.stdout)
+            .expect("Invalid UTF-8 output")
+            .to_string()
+    }
+
+    #[test]
+    fn test_trimadapter() {
+        // 01 - built-in adapters: full, partial (3' end) and mismatched matches
+        let args: Vec<&str> = "trimadapter -I tests/data/adapter/reads.fastq -b truseq -b nextera"
+            .split_whitespace()
+            .collect();
+        let output = run_program_with_args(&args);
+        let expect_content =
+            fs::read("tests/data/adapter/result_for_truseq_nextera.fastq").expect("");
+        assert_eq!(output.as_bytes(), expect_content, "[test] trimadapter - 01");
+
+        // 02 - custom adapters, minimum overlap and minimum length
+        let args: Vec<&str> = "trimadapter -I tests/data/adapter/reads.fastq -b truseq -a tests/data/adapter/custom.fasta -m 10 -O 5"
+            .split_whitespace()
+            .collect();
+        let output = run_program_with_args(&args);
+        let expect_content =
+            fs::read("tests/data/adapter/result_for_custom_min_len.fastq").expect("");
+        assert_eq!(output.as_bytes(), expect_content, "[test] trimadapter - 02");
+    }
+
+    #[test]
+    fn test_detectadapter() {
+        // 01 - assemble the adapter from the read ends
+        let args: Vec<&str> = "detectadapter -I tests/data/adapter/unknown.fastq"
+            .split_whitespace()
+            .collect();
+        let output = run_program_with_args(&args);
+        let expect_content = fs::read("tests/data/adapter/result_for_detect.fasta").expect("");
+        assert_eq!(
+            output.as_bytes(),
+            expect_content,
+            "[test] detectadapter - 01"
+        );
+
+        // 02 - the output is the input of trimadapter
+        let adapter_fa = std::env::temp_dir().join("seqtk_rs_test_detected_adapter.fasta");
+        fs::write(&adapter_fa, output).expect("");
+        let args: Vec<&str> = vec![
+            "trimadapter",
+            "-I",
+            "tests/data/adapter/unknown.fastq",
+            "-a",
+            adapter_fa.to_str().unwrap(),
+        ];
+        let output = run_program_with_args(&args);
+        let expect_content =
+            fs::read("tests/data/adapter/result_for_detect_trimmed.fastq").expect("");
+        assert_eq!(
+            output.as_bytes(),
+            expect_content,
+            "[test] detectadapter - 02"
+        );
+
+        // 03 - the matched built-in adapter
+        let args: Vec<&str> = "detectadapter -I tests/data/adapter/unknown.fastq --match-builtin"
+            .split_whitespace()
+            .collect();
+        let output = run_program_with_args(&args);
+        assert_eq!(
+            output, ">Nextera detected=CTGTCTCTTATACACATCT\nCTGTCTCTTATACACATCT\n",
+            "[test] detectadapter - 03"
+        );
+    }
+
+    #[test]
+    fn test_trimadapter_paired() {
+        // 01 - cut at the insert boundary, interleaved output
+        let args: Vec<&str> =
+            "trimadapter -I tests/data/adapter/pe_R1.fastq -2 tests/data/adapter/pe_R2.fastq"
+                .split_whitespace()
+                .collect();
+        let output = run_program_with_args(&args);
+        let expect_content =
+            fs::read("tests/data/adapter/result_for_pe_interleaved.fastq").expect("");
+        assert_eq!(
+            output.as_bytes(),
+            expect_content,
+            "[test] trimadapter paired - 01"
+        );
+
+        // 02 - adapters for the inserts too short to overlap, pairs discarded together
+        let out1 = std::env::temp_dir().join("seqtk_rs_test_pe_R1.fastq");
+        let out2 = std::env::temp_dir().join("seqtk_rs_test_pe_R2.fastq");
+        let args: Vec<&str> = vec![
+            "trimadapter",
+            "-I",
+            "tests/data/adapter/pe_R1.fastq",
+            "-2",
+            "tests/data/adapter/pe_R2.fastq",
+            "-b",
+            "truseq",
+            "-m",
+            "15",
+            "--out1",
+            out1.to_str().unwrap(),
+            "--out2",
+            out2.to_str().unwrap(),
+        ];
+        run_program_with_args(&args);
+        let expect_content = fs::read("tests/data/adapter/result_for_pe_R1.fastq").expect("");
+        assert_eq!(
+            fs::read(&out1).expect(""),
+            expect_content,
+            "[test] trimadapter paired - 02 R1"
+        );
+        let expect_content = fs::read("tests/data/adapter/result_for_pe_R2.fastq").expect("");
+        assert_eq!(
+            fs::read(&out2).expect(""),
+            expect_content,
+            "[test] trimadapter paired - 02 R2"
+        );
+
+        // 03 - poly-G tails of both mates, pairs kept together
+        let args: Vec<&str> =
+            "trimadapter -I tests/data/poly/polyg_R1.fastq -2 tests/data/poly/polyg_R2.fastq --poly-x G"
+                .split_whitespace()
+                .collect();
+        let output = run_program_with_args(&args);
+        let expect_content = fs::read("tests/data/poly/result_for_pe_poly_g.fastq").expect("");
+        assert_eq!(
+            output.as_bytes(),
+            expect_content,
+            "[test] trimadapter paired - 03"
+        );
+    }
+}
@@ -0,0 +1,68 @@
+#[cfg(test)]
+mod tests {
+    use std::process::Command;
+    use std::{fs, str};
+
+    fn run_program_with_args(args: &[&str]) -> String {
+        let output = Command::new("cargo")
+            .arg("run")
+            .args(args)
+            .output()
+            .expect("Failed to execute command");
+
+        str::from_utf8(&output.stdout)
+            .expect("Invalid UTF-8 output")
+            .to_string()
+    }
+
+    #[test]
+    fn test_bed() {
+        // 01 - merge (contained and book-ended intervals)
+        let args: Vec<&str> = "bed merge tests/data/bed/a.bed"
+            .split_whitespace()
+            .collect();
+        let output = run_program_with_args(&args);
+        let expect_content = fs::read("tests/data/bed/result_for_merge.bed").expect("");
+        assert_eq!(output.as_bytes(), expect_content, "[test] bed - 01");
+
+        // 02 - subtract
+        let args: Vec<&str> = "bed subtract tests/data/bed/a.bed -b tests/data/bed/b.bed"
+            .split_whitespace()
+            .collect();
+        let output = run_program_with_args(&args);
+        let expect_content = fs::read("tests/data/bed/result_for_subtract.bed").expect("");
+        assert_eq!(output.as_bytes(), expect_content, "[test] bed - 02");
+
+        // 03 - complement by the FASTA lengths
+        let args: Vec<&str> = "bed complement tests/data/bed/a.bed -g tests/data/chr.fasta"
+            .split_whitespace()
+            .collect();
+        let output = run_program_with_args(&args);
+        let expect_content = fs::read("tests/data/bed/result_for_complement.bed").expect("");
+        assert_eq!(output.as_bytes(), expect_content, "[test] bed - 03");
+
+        // 04 - strand-aware flank clipped to the FASTA lengths
+        let args: Vec<&str> = "bed flank tests/data/bed/a.bed -l 3 -r 5 -s -g tests/data/chr.fasta"
+            .split_whitespace()
+            .collect();
+        let output = run_program_with_args(&args);
+        let expect_content = fs::read("tests/data/bed/result_for_flank.bed").expect("");
+        assert_eq!(output.as_bytes(), expect_content, "[test] bed - 04");
+
+        // 05 - header lines (track, browser and #) are skipped and whitespace-delimited lines are accepted
+        let args: Vec<&str> = "bed merge tests/data/bed/chr_header.bed"
+            .split_whitespace()
+            .collect();
+        let output = run_program_with_args(&args);
+        let expect_content = fs::read("tests/data/chr.bed").expect("");
+        assert_eq!(output.as_bytes(), expect_content, "[test] bed - 05");
+
+        // 06 - RepeatMasker .out filtered by the repeat class/family
+        let args: Vec<&str> = "bed merge tests/data/rmsk/chr.fasta.out --repeat-class LINE --repeat-class Simple_repeat"
+            .split_whitespace()
+            .collect();
+        let output = run_program_with_args(&args);
+        let expect_content = fs::read("tests/data/rmsk/result_for_merge_classes.bed").expect("");
+        assert_eq!(output.as_bytes(), expect_content, "[test] bed - 06");
+    }
+}
@@ -0,0 +1,58 @@
+#[cfg(test)]
+mod tests {
+    use std::process::Command;
+    use std::{fs, str};
+
+    fn run_program_with_args(args: &[&str]) -> String {
+        let output = Command::new("cargo")
+            .arg("run")
+            .args(args)
+            .output()
+            .expect("Failed to execute command");
+
+        str::from_utf8(&output.stdout)
+            .expect("Invalid UTF-8 output")
+            .to_string()
+    }
+
+    #[test]
+    fn test_cutn() {
+        // 01 - split at N gaps of at least 3 bases
+        let args: Vec<&str> = "cutn -A tests/data/cutn/scaffolds.fasta -n 3"
+            .split_whitespace()
+            .collect();
+        let output = run_program_with_args(&args);
+        let expect_content = fs::read("tests/data/cutn/result_for_n3.fasta").expect("");
+        assert_eq!(output.as_bytes(), expect_content, "[test] cutn - 01");
+
+        // 02 - gaps at the ends, with the gap BED and AGP
+        let gap_bed = std::env::temp_dir().join("seqtk_rs_test_cutn_gaps.bed");
+        let agp = std::env::temp_dir().join("seqtk_rs_test_cutn.agp");
+        let args: Vec<&str> = vec![
+            "cutn",
+            "-A",
+            "tests/data/cutn/scaffolds.fasta",
+            "-n",
+            "2",
+            "-g",
+            gap_bed.to_str().unwrap(),
+            "--agp",
+            agp.to_str().unwrap(),
+        ];
+        let output = run_program_with_args(&args);
+        let expect_content = fs::read("tests/data/cutn/result_for_n2.fasta").expect("");
+        assert_eq!(output.as_bytes(), expect_content, "[test] cutn - 02");
+        let expect_content = fs::read("tests/data/cutn/result_for_n2_gaps.bed").expect("");
+        assert_eq!(
+            fs::read(&gap_bed).expect(""),
+            expect_content,
+            "[test] cutn - 02 gap BED"
+        );
+        let expect_content = fs::read("tests/data/cutn/result_for_n2.agp").expect("");
+        assert_eq!(
+            fs::read(&agp).expect(""),
+            expect_content,
+            "[test] cutn - 02 AGP"
+        );
+    }
+}
@@ -0,0 +1,40 @@
+#[cfg(test)]
+mod tests {
+    use std::process::Command;
+    use std::{fs, str};
+
+    fn run_program_with_args(args: &[&str]) -> String {
+        let output = Command::new("cargo")
+            .arg("run")
+            .args(args)
+            .output()
+            .expect("Failed to execute command");
+
+        str::from_utf8(&output.stdout)
+            .expect("Invalid UTF-8 output")
+            .to_string()
+    }
+
+    #[test]
+    fn test_dedup() {
+        // 01 - UMIs with 1 mismatch are collapsed by the directional adjacency
+        let args = ["dedup", "-I", "tests/data/dedup/reads.fastq"];
+        let output = run_program_with_args(&args);
+        let expected_output = fs::read_to_string("tests/data/dedup/result_d1.fastq").unwrap();
+        assert_eq!(output, expected_output, "[test] dedup - 01");
+
+        // 02 - exact UMIs, families of at least 2 reads
+        let args = [
+            "dedup",
+            "-I",
+            "tests/data/dedup/reads.fastq",
+            "-d",
+            "0",
+            "-m",
+            "2",
+        ];
+        let output = run_program_with_args(&args);
+        let expected_output = fs::read_to_string("tests/data/dedup/result_d0_m2.fastq").unwrap();
+        assert_eq!(output, expected_output, "[test] dedup - 02");
+    }
+}
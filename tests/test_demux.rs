@@ -0,0 +1,85 @@
+#[cfg(test)]
+mod tests {
+    use std::process::Command;
+    use std::{fs, str};
+
+    fn run_program_with_args(args: &[&str]) -> String {
+        let output = Command::new("cargo")
+            .arg("run")
+            .args(args)
+            .output()
+            .expect("Failed to execute command");
+
+        str::from_utf8(&output.stdout)
+            .expect("Invalid UTF-8 output")
+            .to_string()
+    }
+
+    fn assert_same_files(out_dir: &std::path::Path, expected_dir: &str, files: &[&str], msg: &str) {
+        for file in files {
+            let output = fs::read_to_string(out_dir.join(file)).unwrap();
+            let expected_output = fs::read_to_string(format!("{}/{}", expected_dir, file)).unwrap();
+            assert_eq!(output, expected_output, "{} ({})", msg, file);
+        }
+    }
+
+    #[test]
+    fn test_demux() {
+        // 01 - paired-end, dual barcodes in the headers
+        let out_dir = std::env::temp_dir().join("demux_header");
+        let args = [
+            "demux",
+            "-I",
+            "tests/data/demux/R1.fastq",
+            "-2",
+            "tests/data/demux/R2.fastq",
+            "-s",
+            "tests/data/demux/samples.tsv",
+            "-o",
+            out_dir.to_str().unwrap(),
+        ];
+        let output = run_program_with_args(&args);
+        assert_eq!(output, "", "[test] demux - 01");
+        let mut files = Vec::new();
+        for name in ["S1", "S2", "S3", "undetermined", "ambiguous"] {
+            files.push(format!("{}_R1.fastq", name));
+            files.push(format!("{}_R2.fastq", name));
+        }
+        let files: Vec<&str> = files.iter().map(|f| f.as_str()).collect();
+        assert_same_files(
+            &out_dir,
+            "tests/data/demux/result_header",
+            &files,
+            "[test] demux - 01",
+        );
+
+        // 02 - single-end, inline barcodes removed from the assigned reads
+        let out_dir = std::env::temp_dir().join("demux_inline");
+        let args = [
+            "demux",
+            "-I",
+            "tests/data/demux/inline.fastq",
+            "-s",
+            "tests/data/demux/inline.tsv",
+            "-l",
+            "inline",
+            "-k",
+            "1",
+            "-o",
+            out_dir.to_str().unwrap(),
+        ];
+        let output = run_program_with_args(&args);
+        assert_eq!(output, "", "[test] demux - 02");
+        assert_same_files(
+            &out_dir,
+            "tests/data/demux/result_inline",
+            &[
+                "A.fastq",
+                "B.fastq",
+                "undetermined.fastq",
+                "ambiguous.fastq",
+            ],
+            "[test] demux - 02",
+        );
+    }
+}
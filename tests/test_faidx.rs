@@ -0,0 +1,28 @@
+#[cfg(test)]
+mod tests {
+    use std::process::Command;
+    use std::{fs, str};
+
+    fn run_program_with_args(args: &[&str]) -> String {
+        let output = Command::new("cargo")
+            .arg("run")
+            .args(args)
+            .output()
+            .expect("Failed to execute command");
+
+        str::from_utf8(&output.stdout)
+            .expect("Invalid UTF-8 output")
+            .to_string()
+    }
+
+    #[test]
+    fn test_faidx_bgzf() {
+        // 01 - regions from BGZF-compressed FASTA with .fai and .gzi
+        let args: Vec<&str> = "faidx tests/data/faidx/long.fasta.gz 35eb0273-89e2-4093-98ed-d81cbdafcac7:32-82 af4eb609-12d7-4672-ab46-7a16476ac304 4f7f9bf5-2a3e-495c-88e9-26d36ce917ea:1027-1077"
+            .split_whitespace()
+            .collect();
+        let output = run_program_with_args(&args);
+        let expect_content = fs::read("tests/data/faidx/result_for_long_regions.fasta").expect("");
+        assert_eq!(output.as_bytes(), expect_content, "[test] faidx - 01");
+    }
+}
@@ -0,0 +1,35 @@
+#[cfg(test)]
+mod tests {
+    use std::process::Command;
+    use std::{fs, str};
+
+    fn run_program_with_args(args: &[&str]) -> String {
+        let output = Command::new("cargo")
+            .arg("run")
+            .args(args)
+            .output()
+            .expect("Failed to execute command");
+
+        str::from_utf8(&output.stdout)
+            .expect("Invalid UTF-8 output")
+            .to_string()
+    }
+
+    #[test]
+    fn test_getfeat() {
+        // 01 - CDS from GFF3 (joined by parent, minus strand reverse-complemented)
+        let args: Vec<&str> = "getfeat -A tests/data/chr.fasta -g tests/data/getfeat/chr.gff3"
+            .split_whitespace()
+            .collect();
+        let output = run_program_with_args(&args);
+        let expect_content = fs::read("tests/data/getfeat/result_for_cds.fasta").expect("");
+        assert_eq!(output.as_bytes(), expect_content, "[test] getfeat - 01");
+
+        // 02 - CDS from GTF
+        let args: Vec<&str> = "getfeat -A tests/data/chr.fasta -g tests/data/getfeat/chr.gtf"
+            .split_whitespace()
+            .collect();
+        let output = run_program_with_args(&args);
+        assert_eq!(output.as_bytes(), expect_content, "[test] getfeat - 02");
+    }
+}
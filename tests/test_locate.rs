@@ -0,0 +1,38 @@
+#[cfg(test)]
+mod tests {
+    use std::process::Command;
+    use std::{fs, str};
+
+    fn run_program_with_args(args: &[&str]) -> String {
+        let output = Command::new("cargo")
+            .arg("run")
+            .args(args)
+            .output()
+            .expect("Failed to execute command");
+
+        str::from_utf8(&output.stdout)
+            .expect("Invalid UTF-8 output")
+            .to_string()
+    }
+
+    #[test]
+    fn test_locate() {
+        // 01 - IUPAC motif on the command line and exact motifs from FASTA, on both strands
+        let args: Vec<&str> =
+            "locate -A tests/data/locate/seqs.fasta -m GRAWYC -f tests/data/locate/motifs.fasta"
+                .split_whitespace()
+                .collect();
+        let output = run_program_with_args(&args);
+        let expect_content = fs::read("tests/data/locate/result_for_iupac.bed").expect("");
+        assert_eq!(output.as_bytes(), expect_content, "[test] locate - 01");
+
+        // 02 - FASTQ, 1 mismatch
+        let args: Vec<&str> =
+            "locate -I tests/data/locate/seqs.fastq -f tests/data/locate/motifs.fasta -k 1"
+                .split_whitespace()
+                .collect();
+        let output = run_program_with_args(&args);
+        let expect_content = fs::read("tests/data/locate/result_for_k1.bed").expect("");
+        assert_eq!(output.as_bytes(), expect_content, "[test] locate - 02");
+    }
+}
@@ -0,0 +1,44 @@
+#[cfg(test)]
+mod tests {
+    use std::process::Command;
+    use std::{fs, str};
+
+    fn run_program_with_args(args: &[&str]) -> String {
+        let output = Command::new("cargo")
+            .arg("run")
+            .args(args)
+            .output()
+            .expect("Failed to execute command");
+
+        str::from_utf8(&output.stdout)
+            .expect("Invalid UTF-8 output")
+            .to_string()
+    }
+
+    #[test]
+    fn test_maskbed() {
+        // 01 - soft-masked (lowercase) runs
+        let args: Vec<&str> = "maskbed -A tests/data/maskbed/scaffolds.fasta"
+            .split_whitespace()
+            .collect();
+        let output = run_program_with_args(&args);
+        let expect_content = fs::read("tests/data/maskbed/result_for_lower.bed").expect("");
+        assert_eq!(output.as_bytes(), expect_content, "[test] maskbed - 01");
+
+        // 02 - N gaps of at least 3 bases
+        let args: Vec<&str> = "maskbed -A tests/data/maskbed/scaffolds.fasta -c n -l 3"
+            .split_whitespace()
+            .collect();
+        let output = run_program_with_args(&args);
+        let expect_content = fs::read("tests/data/maskbed/result_for_n_gaps.bed").expect("");
+        assert_eq!(output.as_bytes(), expect_content, "[test] maskbed - 02");
+
+        // 03 - soft-masking by the exported BED restores the input
+        let args: Vec<&str> = "seq -A tests/data/maskbed/scaffolds.fasta --uppercases -M tests/data/maskbed/result_for_lower.bed"
+            .split_whitespace()
+            .collect();
+        let output = run_program_with_args(&args);
+        let expect_content = fs::read("tests/data/maskbed/scaffolds.fasta").expect("");
+        assert_eq!(output.as_bytes(), expect_content, "[test] maskbed - 03");
+    }
+}
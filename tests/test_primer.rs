@@ -0,0 +1,36 @@
+#[cfg(test)]
+mod tests {
+    use std::process::Command;
+    use std::{fs, str};
+
+    fn run_program_with_args(args: &[&str]) -> String {
+        let output = Command::new("cargo")
+            .arg("run")
+            .args(args)
+            .output()
+            .expect("Failed to execute command");
+
+        str::from_utf8(&output.stdout)
+            .expect("Invalid UTF-8 output")
+            .to_string()
+    }
+
+    #[test]
+    fn test_trimprimer() {
+        // 01 - both orientations (flipped to forward), the amplicon end and a mismatch
+        let args: Vec<&str> = "trimprimer -I tests/data/primer/amplicons.fastq -p tests/data/primer/primers.fasta -k 1"
+            .split_whitespace()
+            .collect();
+        let output = run_program_with_args(&args);
+        let expect_content = fs::read("tests/data/primer/result_for_k1.fastq").expect("");
+        assert_eq!(output.as_bytes(), expect_content, "[test] trimprimer - 01");
+
+        // 02 - exact matches only, reads without primers discarded
+        let args: Vec<&str> = "trimprimer -I tests/data/primer/amplicons.fastq -p tests/data/primer/primers.fasta -k 0 -d"
+            .split_whitespace()
+            .collect();
+        let output = run_program_with_args(&args);
+        let expect_content = fs::read("tests/data/primer/result_for_k0_discard.fastq").expect("");
+        assert_eq!(output.as_bytes(), expect_content, "[test] trimprimer - 02");
+    }
+}
@@ -0,0 +1,85 @@
+#[cfg(test)]
+mod tests {
+    use std::process::Command;
+    use std::{fs, str};
+
+    fn run_program_with_args(args: &[&str]) -> String {
+        let output = Command::new("cargo")
+            .arg("run")
+            .args(args)
+            .output()
+            .expect("Failed to execute command");
+
+        str::from_utf8(&output.stdout)
+            .expect("Invalid UTF-8 output")
+            .to_string()
+    }
+
+    #[test]
+    fn test_qctrim_fixed() {
+        // 01 - fixed trimming of both ends, quality trimming, then capping the length
+        let args: Vec<&str> = "qctrim tests/data/long.fastq -b 5 -e 3 -L 40"
+            .split_whitespace()
+            .collect();
+        let output = run_program_with_args(&args);
+        let expect_content =
+            fs::read("tests/data/qctrim/result_for_fixed_max_len.fastq").expect("");
+        assert_eq!(output.as_bytes(), expect_content, "[test] qctrim - 01");
+
+        // 02 - no quality trimming, uniform length (shorter reads are discarded)
+        let args: Vec<&str> = "qctrim tests/data/long.fastq -q 0 --truncate-to 100"
+            .split_whitespace()
+            .collect();
+        let output = run_program_with_args(&args);
+        let expect_content = fs::read("tests/data/qctrim/result_for_truncate.fastq").expect("");
+        assert_eq!(output.as_bytes(), expect_content, "[test] qctrim - 02");
+    }
+
+    #[test]
+    fn test_qctrim_methods() {
+        // 01 - Trimmomatic-style LEADING, TRAILING and SLIDINGWINDOW
+        let args: Vec<&str> =
+            "qctrim tests/data/long.fastq --method window -q 20 --leading 3 --trailing 3"
+                .split_whitespace()
+                .collect();
+        let output = run_program_with_args(&args);
+        let expect_content = fs::read("tests/data/qctrim/result_for_window.fastq").expect("");
+        assert_eq!(
+            output.as_bytes(),
+            expect_content,
+            "[test] qctrim methods - 01"
+        );
+
+        // 02 - truncation at the expected errors, short reads discarded
+        let args: Vec<&str> = "qctrim tests/data/long.fastq --method max-ee --max-ee 2 -d"
+            .split_whitespace()
+            .collect();
+        let output = run_program_with_args(&args);
+        let expect_content =
+            fs::read("tests/data/qctrim/result_for_max_ee_discard.fastq").expect("");
+        assert_eq!(
+            output.as_bytes(),
+            expect_content,
+            "[test] qctrim methods - 02"
+        );
+    }
+
+    #[test]
+    fn test_qctrim_poly() {
+        // 01 - poly-G tails with a mismatch, a tail shorter than the minimum is kept
+        let args: Vec<&str> = "qctrim tests/data/poly/polyg.fastq --poly-x G -m 0"
+            .split_whitespace()
+            .collect();
+        let output = run_program_with_args(&args);
+        let expect_content = fs::read("tests/data/poly/result_for_qctrim_poly_g.fastq").expect("");
+        assert_eq!(output.as_bytes(), expect_content, "[test] qctrim poly - 01");
+
+        // 02 - tails of any base, short reads discarded
+        let args: Vec<&str> = "qctrim tests/data/poly/polyg.fastq --poly-x x -d -m 30"
+            .split_whitespace()
+            .collect();
+        let output = run_program_with_args(&args);
+        let expect_content = fs::read("tests/data/poly/result_for_qctrim_poly_x.fastq").expect("");
+        assert_eq!(output.as_bytes(), expect_content, "[test] qctrim poly - 02");
+    }
+}
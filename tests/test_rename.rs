@@ -0,0 +1,49 @@
+#[cfg(test)]
+mod tests {
+    use std::process::Command;
+    use std::{fs, str};
+
+    fn run_program_with_args(args: &[&str]) -> String {
+        let output = Command::new("cargo")
+            .arg("run")
+            .args(args)
+            .output()
+            .expect("Failed to execute command");
+
+        str::from_utf8(&output.stdout)
+            .expect("Invalid UTF-8 output")
+            .to_string()
+    }
+
+    #[test]
+    fn test_rename() {
+        // 01 - chr1 to 1
+        let args: Vec<&str> = "rename -A tests/data/chr.fasta --to-ensembl"
+            .split_whitespace()
+            .collect();
+        let output = run_program_with_args(&args);
+        let expect_content = fs::read("tests/data/alias/chr_ensembl.fasta").expect("");
+        assert_eq!(output.as_bytes(), expect_content, "[test] rename - 01");
+
+        // 02 - 1 to chr1
+        let args: Vec<&str> = "rename -A tests/data/alias/chr_ensembl.fasta --to-ucsc"
+            .split_whitespace()
+            .collect();
+        let output = run_program_with_args(&args);
+        let expect_content = fs::read("tests/data/chr.fasta").expect("");
+        assert_eq!(output.as_bytes(), expect_content, "[test] rename - 02");
+    }
+
+    #[test]
+    fn test_mask_with_chr_alias() {
+        // 01 - BED with chr1 masks the record 1
+        let args: Vec<&str> =
+            "seq -A tests/data/alias/chr_ensembl.fasta -M tests/data/chr.bed --chr-alias"
+                .split_whitespace()
+                .collect();
+        let output = run_program_with_args(&args);
+        let expect_content =
+            fs::read("tests/data/alias/result_for_mask_chr_alias.fasta").expect("");
+        assert_eq!(output.as_bytes(), expect_content, "[test] alias - 01");
+    }
+}
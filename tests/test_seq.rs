@@ -172,4 +172,20 @@ mod tests {
         let expect_content = fs::read("tests/data/seq/fq2fq_05.fastq").expect("");
         assert_eq!(output.as_bytes(), expect_content, "[test] fa -> fq - 05");
     }
+
+    #[test]
+    fn test_seq_mask_repeatmasker() {
+        // 01 - hard-mask LINE/L1 repeats of a RepeatMasker .out file with 2 bp padding
+        let args: Vec<&str> = "seq -A tests/data/chr.fasta -M tests/data/rmsk/chr.fasta.out --repeat-class LINE/L1 --mask-padding 2 --mask-char N".split_whitespace().collect();
+        let output = run_program_with_args(&args);
+        let expect_content =
+            fs::read("tests/data/rmsk/result_for_mask_l1_padding.fasta").expect("");
+        assert_eq!(output.as_bytes(), expect_content, "[test] rmsk - 01");
+        // 02 - the padding may extend beyond the record in the strict mode
+        let args: Vec<&str> = "seq -A tests/data/chr.fasta -M tests/data/rmsk/chr.fasta.out --repeat-class LINE/L1 --mask-padding 5 --mask-char N --strict-bed".split_whitespace().collect();
+        let output = run_program_with_args(&args);
+        let expect_content =
+            fs::read("tests/data/rmsk/result_for_mask_l1_padding_strict.fasta").expect("");
+        assert_eq!(output.as_bytes(), expect_content, "[test] rmsk - 02");
+    }
 }
@@ -0,0 +1,44 @@
+#[cfg(test)]
+mod tests {
+    use std::process::Command;
+    use std::{fs, str};
+
+    fn run_program_with_args(args: &[&str]) -> String {
+        let output = Command::new("cargo")
+            .arg("run")
+            .args(args)
+            .output()
+            .expect("Failed to execute command");
+
+        str::from_utf8(&output.stdout)
+            .expect("Invalid UTF-8 output")
+            .to_string()
+    }
+
+    #[test]
+    fn test_select() {
+        // 01 - FASTQ, the best reads by length and quality within --target-bases (in the input order)
+        let args: Vec<&str> = "select -I tests/data/select/reads.fastq -t 60"
+            .split_whitespace()
+            .collect();
+        let output = run_program_with_args(&args);
+        let expect_content = fs::read("tests/data/select/result_for_t60.fastq").expect("");
+        assert_eq!(output.as_bytes(), expect_content, "[test] select - 01");
+
+        // 02 - FASTQ, by length only (--quality-weight 0), a read skipped by the budget
+        let args: Vec<&str> = "select -I tests/data/select/reads.fastq -t 55 --quality-weight 0"
+            .split_whitespace()
+            .collect();
+        let output = run_program_with_args(&args);
+        let expect_content = fs::read("tests/data/select/result_for_t55_len_only.fastq").expect("");
+        assert_eq!(output.as_bytes(), expect_content, "[test] select - 02");
+
+        // 03 - FASTA, scored by length
+        let args: Vec<&str> = "select -A tests/data/select/reads.fasta -t 55"
+            .split_whitespace()
+            .collect();
+        let output = run_program_with_args(&args);
+        let expect_content = fs::read("tests/data/select/result_for_t55.fasta").expect("");
+        assert_eq!(output.as_bytes(), expect_content, "[test] select - 03");
+    }
+}
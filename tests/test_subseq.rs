@@ -0,0 +1,61 @@
+#[cfg(test)]
+mod tests {
+    use std::process::Command;
+    use std::{fs, str};
+
+    fn run_program_with_args(args: &[&str]) -> String {
+        let output = Command::new("cargo")
+            .arg("run")
+            .args(args)
+            .output()
+            .expect("Failed to execute command");
+
+        str::from_utf8(&output.stdout)
+            .expect("Invalid UTF-8 output")
+            .to_string()
+    }
+
+    #[test]
+    fn test_subseq() {
+        // 01 - fasta + bed
+        let args: Vec<&str> = "subseq -A tests/data/chr.fasta -r tests/data/chr.bed"
+            .split_whitespace()
+            .collect();
+        let output = run_program_with_args(&args);
+        let expect_content = fs::read("tests/data/subseq/fa_bed.fasta").expect("");
+        assert_eq!(output.as_bytes(), expect_content, "[test] subseq - 01");
+
+        // 02 - fastq + bed (quality is sliced too)
+        let args: Vec<&str> = "subseq -I tests/data/chr.fastq -r tests/data/chr.bed"
+            .split_whitespace()
+            .collect();
+        let output = run_program_with_args(&args);
+        let expect_content = fs::read("tests/data/subseq/fq_bed.fastq").expect("");
+        assert_eq!(output.as_bytes(), expect_content, "[test] subseq - 02");
+
+        // 03 - fastq + name list
+        let args: Vec<&str> = "subseq -I tests/data/chr.fastq -n tests/data/subseq/names.txt"
+            .split_whitespace()
+            .collect();
+        let output = run_program_with_args(&args);
+        let expect_content = fs::read("tests/data/subseq/fq_names.fastq").expect("");
+        assert_eq!(output.as_bytes(), expect_content, "[test] subseq - 03");
+
+        // 04 - fastq + bed6 (named records, minus strand is reverse-complemented)
+        let args: Vec<&str> = "subseq -I tests/data/chr.fastq -r tests/data/subseq/chr_strand.bed"
+            .split_whitespace()
+            .collect();
+        let output = run_program_with_args(&args);
+        let expect_content = fs::read("tests/data/subseq/fq_bed6.fastq").expect("");
+        assert_eq!(output.as_bytes(), expect_content, "[test] subseq - 04");
+
+        // 05 - fasta + bed12 (blocks are joined and reverse-complemented)
+        let args: Vec<&str> =
+            "subseq -A tests/data/chr.fasta -r tests/data/subseq/chr_bed12.bed --split"
+                .split_whitespace()
+                .collect();
+        let output = run_program_with_args(&args);
+        let expect_content = fs::read("tests/data/subseq/fa_bed12_split.fasta").expect("");
+        assert_eq!(output.as_bytes(), expect_content, "[test] subseq - 05");
+    }
+}
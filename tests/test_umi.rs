@@ -0,0 +1,84 @@
+#[cfg(test)]
+mod tests {
+    use std::process::Command;
+    use std::{fs, str};
+
+    fn run_program_with_args(args: &[&str]) -> String {
+        let output = Command::new("cargo")
+            .arg("run")
+            .args(args)
+            .output()
+            .expect("Failed to execute command");
+
+        str::from_utf8(&output.stdout)
+            .expect("Invalid UTF-8 output")
+            .to_string()
+    }
+
+    #[test]
+    fn test_umi() {
+        // 01 - single-end, with skipped bases and UMI qualities
+        let args = [
+            "umi",
+            "-I",
+            "tests/data/umi/R1.fastq",
+            "-p",
+            "NNNNNNXX",
+            "--umi-qual",
+        ];
+        let output = run_program_with_args(&args);
+        let expected_output = fs::read_to_string("tests/data/umi/result_se.fastq").unwrap();
+        assert_eq!(output, expected_output, "[test] umi - 01");
+
+        // 02 - paired-end, UMIs of both reads with a custom separator
+        let args = [
+            "umi",
+            "-I",
+            "tests/data/umi/R1.fastq",
+            "-2",
+            "tests/data/umi/R2.fastq",
+            "-p",
+            "NNNNXX",
+            "--pattern2",
+            "NNNN",
+            "-s",
+            ":",
+        ];
+        let output = run_program_with_args(&args);
+        let expected_output = fs::read_to_string("tests/data/umi/result_pe.fastq").unwrap();
+        assert_eq!(output, expected_output, "[test] umi - 02");
+
+        // 03 - UMI from the index read, pairs to --out1/--out2
+        let out1 = std::env::temp_dir().join("umi_index_R1.fastq");
+        let out2 = std::env::temp_dir().join("umi_index_R2.fastq");
+        let args = [
+            "umi",
+            "-I",
+            "tests/data/umi/R1.fastq",
+            "-2",
+            "tests/data/umi/R2.fastq",
+            "-u",
+            "tests/data/umi/I1.fastq",
+            "-p",
+            "NNNNNNNN",
+            "--out1",
+            out1.to_str().unwrap(),
+            "--out2",
+            out2.to_str().unwrap(),
+        ];
+        let output = run_program_with_args(&args);
+        assert_eq!(output, "", "[test] umi - 03");
+        let expect_content = fs::read("tests/data/umi/result_index_R1.fastq").expect("");
+        assert_eq!(
+            fs::read(&out1).expect(""),
+            expect_content,
+            "[test] umi - 03 R1"
+        );
+        let expect_content = fs::read("tests/data/umi/result_index_R2.fastq").expect("");
+        assert_eq!(
+            fs::read(&out2).expect(""),
+            expect_content,
+            "[test] umi - 03 R2"
+        );
+    }
+}